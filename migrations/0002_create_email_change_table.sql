@@ -0,0 +1,20 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE email_change (
+    id              SERIAL PRIMARY KEY,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    old_email       VARCHAR(255) NOT NULL,
+    new_email       VARCHAR(255) NOT NULL,
+    confirm_token   VARCHAR(255) UNIQUE NOT NULL,
+    undo_token      VARCHAR(255) UNIQUE NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    confirmed_at    TIMESTAMPTZ,
+    undone_at       TIMESTAMPTZ
+);
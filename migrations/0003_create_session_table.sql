@@ -0,0 +1,24 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE session (
+    id              SERIAL PRIMARY KEY,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    write_key       VARCHAR(255) UNIQUE NOT NULL,
+    read_key        VARCHAR(255) UNIQUE NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    elevated_until  TIMESTAMPTZ
+);
+
+INSERT INTO session (account_id, write_key, read_key)
+SELECT id, write_key, read_key FROM account;
+
+ALTER TABLE account
+    DROP COLUMN write_key,
+    DROP COLUMN read_key;
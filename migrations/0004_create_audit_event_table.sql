@@ -0,0 +1,30 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE audit_event (
+    id              SERIAL PRIMARY KEY,
+    account_id      INTEGER,
+    action          VARCHAR(64) NOT NULL,
+    outcome         VARCHAR(64) NOT NULL,
+    ip_address      VARCHAR(255),
+    user_agent      VARCHAR(1024),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_event_account_id_index ON audit_event (account_id, created_at DESC);
+
+CREATE FUNCTION audit_event_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_event is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_event_append_only
+BEFORE UPDATE OR DELETE ON audit_event
+FOR EACH ROW EXECUTE FUNCTION audit_event_append_only();
@@ -0,0 +1,27 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE device (
+    id              SERIAL PRIMARY KEY,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    ip_address      VARCHAR(255),
+    user_agent      VARCHAR(1024),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX device_account_id_index ON device (account_id);
+
+ALTER TABLE session
+    ADD COLUMN revoke_token VARCHAR(255) UNIQUE;
+
+UPDATE session SET revoke_token = gen_random_uuid()::TEXT;
+
+ALTER TABLE session
+    ALTER COLUMN revoke_token SET NOT NULL;
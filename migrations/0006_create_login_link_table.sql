@@ -0,0 +1,16 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE login_link (
+    id              SERIAL PRIMARY KEY,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    token_hash      VARCHAR(64) UNIQUE NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    used_at         TIMESTAMPTZ
+);
@@ -0,0 +1,25 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Existing addresses stay in the legacy email column until the rotate_email_keys command has encrypted them.
+
+ALTER TABLE account
+    ALTER COLUMN email DROP NOT NULL,
+    ADD COLUMN email_ciphertext BYTEA,
+    ADD COLUMN email_key_id VARCHAR(64),
+    ADD COLUMN email_index VARCHAR(64) UNIQUE;
+
+DELETE FROM email_change;
+
+ALTER TABLE email_change
+    DROP COLUMN old_email,
+    DROP COLUMN new_email,
+    ADD COLUMN old_email_ciphertext BYTEA NOT NULL,
+    ADD COLUMN new_email_ciphertext BYTEA NOT NULL,
+    ADD COLUMN email_key_id VARCHAR(64) NOT NULL;
@@ -0,0 +1,36 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE role (
+    id              SERIAL PRIMARY KEY,
+    name            VARCHAR(64) UNIQUE NOT NULL
+);
+
+CREATE TABLE permission (
+    id              SERIAL PRIMARY KEY,
+    name            VARCHAR(64) UNIQUE NOT NULL
+);
+
+CREATE TABLE role_permission (
+    role_id         INTEGER NOT NULL REFERENCES role(id) ON DELETE CASCADE,
+    permission_id   INTEGER NOT NULL REFERENCES permission(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE TABLE account_role (
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    role_id         INTEGER NOT NULL REFERENCES role(id) ON DELETE CASCADE,
+    PRIMARY KEY (account_id, role_id)
+);
+
+INSERT INTO role (name) VALUES ('admin');
+INSERT INTO permission (name) VALUES ('roles.manage');
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT role.id, permission.id FROM role, permission WHERE role.name = 'admin';
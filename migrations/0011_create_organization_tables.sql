@@ -0,0 +1,37 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE organization (
+    id              SERIAL PRIMARY KEY,
+    name            VARCHAR(255) NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE membership (
+    organization_id INTEGER NOT NULL REFERENCES organization(id) ON DELETE CASCADE,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    role            VARCHAR(16) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    PRIMARY KEY (organization_id, account_id)
+);
+
+CREATE INDEX membership_account_id_index ON membership(account_id);
+
+CREATE TABLE invitation (
+    id                  SERIAL PRIMARY KEY,
+    organization_id     INTEGER NOT NULL REFERENCES organization(id) ON DELETE CASCADE,
+    email_ciphertext    BYTEA NOT NULL,
+    email_key_id        VARCHAR(64) NOT NULL,
+    email_index         VARCHAR(64) NOT NULL,
+    role                VARCHAR(16) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    token_hash          VARCHAR(64) UNIQUE NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (organization_id, email_index)
+);
+
+ALTER TABLE session ADD COLUMN organization_id INTEGER REFERENCES organization(id) ON DELETE SET NULL;
@@ -0,0 +1,36 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+CREATE TABLE realm (
+    id                  SERIAL PRIMARY KEY,
+    name                VARCHAR(64) UNIQUE NOT NULL,
+    hostname            VARCHAR(255) UNIQUE,
+    registration_open   BOOLEAN NOT NULL DEFAULT TRUE,
+    password_min_length INTEGER NOT NULL DEFAULT 8,
+    cookie_domain       VARCHAR(255)
+);
+
+INSERT INTO realm (name) VALUES ('default');
+
+-- Everything that exists already belongs to the default realm.
+
+ALTER TABLE account
+    ADD COLUMN realm_id INTEGER NOT NULL DEFAULT 1 REFERENCES realm(id),
+    DROP CONSTRAINT account_email_key,
+    DROP CONSTRAINT account_email_index_key,
+    ADD UNIQUE (realm_id, email),
+    ADD UNIQUE (realm_id, email_index);
+
+ALTER TABLE account ALTER COLUMN realm_id DROP DEFAULT;
+
+ALTER TABLE session ADD COLUMN realm_id INTEGER NOT NULL DEFAULT 1 REFERENCES realm(id);
+ALTER TABLE session ALTER COLUMN realm_id DROP DEFAULT;
+
+ALTER TABLE organization ADD COLUMN realm_id INTEGER NOT NULL DEFAULT 1 REFERENCES realm(id);
+ALTER TABLE organization ALTER COLUMN realm_id DROP DEFAULT;
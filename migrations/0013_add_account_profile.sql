@@ -0,0 +1,17 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE account
+    ADD COLUMN display_name VARCHAR(255),
+    ADD COLUMN avatar_url VARCHAR(2048),
+    ADD COLUMN locale VARCHAR(35),
+    ADD COLUMN timezone VARCHAR(64),
+    ADD COLUMN attributes JSONB NOT NULL DEFAULT '{}';
+
+ALTER TABLE realm ADD COLUMN profile_schema JSONB NOT NULL DEFAULT '{"type": "object"}';
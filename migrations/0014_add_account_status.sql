@@ -0,0 +1,21 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE account
+    ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'unverified', 'suspended', 'pending_deletion')),
+    ADD COLUMN suspended_reason VARCHAR(255),
+    ADD COLUMN suspended_until TIMESTAMPTZ,
+    ADD COLUMN deletion_requested_at TIMESTAMPTZ;
+
+ALTER TABLE realm ADD COLUMN email_verification BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO permission (name) VALUES ('accounts.manage');
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT role.id, permission.id FROM role, permission WHERE role.name = 'admin' AND permission.name = 'accounts.manage';
@@ -0,0 +1,26 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Postgres has no UUIDv7 generator before version 18, so the backfill overwrites the timestamp and version bits
+-- of a random UUID.
+
+ALTER TABLE account ADD COLUMN public_id UUID UNIQUE;
+
+UPDATE account SET public_id = encode(
+    set_bit(
+        set_bit(
+            overlay(uuid_send(gen_random_uuid()) placing substring(int8send(floor(extract(epoch FROM clock_timestamp()) * 1000)::BIGINT) FROM 3) FROM 1 FOR 6),
+            52, 1
+        ),
+        53, 1
+    ),
+    'hex'
+)::UUID;
+
+ALTER TABLE account ALTER COLUMN public_id SET NOT NULL;
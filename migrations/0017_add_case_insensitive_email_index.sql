@@ -0,0 +1,16 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Encrypted addresses are kept unique by email_index, which the normalize_emails command recomputes from the
+-- normalized address. Addresses that were never encrypted are looked up case-insensitively here, existing rows may
+-- still differ only by case, so they are only made unique once 0028 has merged them.
+
+ALTER TABLE account DROP CONSTRAINT account_realm_id_email_key;
+
+CREATE INDEX account_realm_id_lower_email_index ON account (realm_id, LOWER(email));
@@ -0,0 +1,28 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE account
+    ADD COLUMN username VARCHAR(64),
+    ADD COLUMN username_changed_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX account_realm_id_lower_username_key ON account (realm_id, LOWER(username));
+
+ALTER TABLE realm
+    ADD COLUMN username_pattern VARCHAR(255) NOT NULL DEFAULT '^[A-Za-z0-9_]{3,32}$',
+    ADD COLUMN reserved_usernames TEXT[] NOT NULL DEFAULT '{admin,administrator,root,support,system}';
+
+-- Usernames are stored lowercase, so the primary key compares them case-insensitively.
+
+CREATE TABLE username_reservation (
+    realm_id        INTEGER NOT NULL REFERENCES realm(id),
+    username        VARCHAR(64) NOT NULL,
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (realm_id, username)
+);
@@ -0,0 +1,33 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE account
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN last_login_at TIMESTAMPTZ,
+    ADD COLUMN last_seen_at TIMESTAMPTZ,
+    ADD COLUMN password_changed_at TIMESTAMPTZ;
+
+-- Existing accounts take their timestamps from the audit log where it has them.
+
+UPDATE account SET created_at = registration.created_at, updated_at = registration.created_at
+FROM (
+    SELECT account_id, MIN(created_at) AS created_at FROM audit_event WHERE action = 'register' GROUP BY account_id
+) AS registration
+WHERE registration.account_id = account.id;
+
+UPDATE account SET last_login_at = login.created_at, last_seen_at = login.created_at
+FROM (
+    SELECT account_id, MAX(created_at) AS created_at FROM audit_event
+    WHERE action IN ('login', 'login_link', 'login_code') AND outcome = 'success'
+    GROUP BY account_id
+) AS login
+WHERE login.account_id = account.id;
+
+UPDATE account SET password_changed_at = created_at;
@@ -0,0 +1,12 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Set when an administrator acted on someone else's account.
+
+ALTER TABLE audit_event ADD COLUMN actor_id INTEGER;
@@ -0,0 +1,17 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE session
+    ADD COLUMN impersonator_id INTEGER REFERENCES account(id) ON DELETE CASCADE,
+    ADD COLUMN expires_at TIMESTAMPTZ;
+
+INSERT INTO permission (name) VALUES ('accounts.impersonate');
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT role.id, permission.id FROM role, permission WHERE role.name = 'admin' AND permission.name = 'accounts.impersonate';
@@ -0,0 +1,10 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE account ADD COLUMN password_algorithm VARCHAR(32) NOT NULL DEFAULT 'bcrypt';
@@ -0,0 +1,23 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+ALTER TABLE realm
+    ADD COLUMN password_history_depth INTEGER NOT NULL DEFAULT 5,
+    ADD COLUMN password_max_age INTEGER;
+
+CREATE TABLE password_history (
+    id                  SERIAL PRIMARY KEY,
+    account_id          INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    password_hash       VARCHAR(255) NOT NULL,
+    password_pepper_id  VARCHAR(64),
+    password_algorithm  VARCHAR(32) NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX password_history_account_id_index ON password_history (account_id);
@@ -0,0 +1,15 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Replaced codes are kept for an hour, so that their attempts still count against the account.
+
+ALTER TABLE login_code
+    ADD COLUMN revoked_at TIMESTAMPTZ;
+
+CREATE INDEX login_code_account_id_index ON login_code (account_id, created_at DESC);
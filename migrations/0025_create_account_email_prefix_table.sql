@@ -0,0 +1,19 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Keyed hashes of the prefixes of the email address, starting at 3 characters, so that encrypted
+-- addresses can be searched by prefix. Existing accounts are indexed by running rotate_email_keys.
+
+CREATE TABLE account_email_prefix (
+    account_id      INTEGER NOT NULL REFERENCES account(id) ON DELETE CASCADE,
+    prefix_index    VARCHAR(64) NOT NULL,
+    PRIMARY KEY (prefix_index, account_id)
+);
+
+CREATE INDEX account_email_prefix_account_id_index ON account_email_prefix (account_id);
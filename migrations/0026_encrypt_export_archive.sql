@@ -0,0 +1,22 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Archives hold the decrypted email address, so they are encrypted like the address itself. Archives
+-- stored before are dropped and reported as failed, a new export can be requested right away.
+
+ALTER TABLE export
+    ADD COLUMN archive_key_id VARCHAR(64);
+
+UPDATE
+    export
+SET
+    status = 'failed',
+    archive = NULL
+WHERE
+    archive IS NOT NULL;
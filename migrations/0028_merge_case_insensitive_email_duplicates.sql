@@ -0,0 +1,67 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Addresses that were never encrypted and only differ by case are merged into the oldest account, like
+-- normalize_emails --merge does for encrypted ones: roles and memberships move over, the others are deleted.
+
+CREATE TEMPORARY TABLE email_duplicate AS
+    SELECT
+        id,
+        MIN(id) OVER (PARTITION BY realm_id, LOWER(email)) AS kept_id
+    FROM
+        account
+    WHERE
+        email IS NOT NULL;
+
+DELETE FROM email_duplicate WHERE id = kept_id;
+
+DO $$
+DECLARE
+    duplicate RECORD;
+BEGIN
+    FOR duplicate IN
+        SELECT
+            account.public_id,
+            kept.public_id AS kept_public_id
+        FROM
+            email_duplicate
+        INNER JOIN
+            account ON account.id = email_duplicate.id
+        INNER JOIN
+            account kept ON kept.id = email_duplicate.kept_id
+        ORDER BY
+            email_duplicate.id
+    LOOP
+        RAISE NOTICE 'Merging account % into account %', duplicate.public_id, duplicate.kept_public_id;
+    END LOOP;
+END $$;
+
+INSERT INTO
+    account_role(account_id, role_id)
+SELECT
+    email_duplicate.kept_id, account_role.role_id
+FROM
+    account_role
+INNER JOIN
+    email_duplicate ON email_duplicate.id = account_role.account_id
+ON CONFLICT DO NOTHING;
+
+INSERT INTO
+    membership(organization_id, account_id, role)
+SELECT
+    membership.organization_id, email_duplicate.kept_id, membership.role
+FROM
+    membership
+INNER JOIN
+    email_duplicate ON email_duplicate.id = membership.account_id
+ON CONFLICT DO NOTHING;
+
+DELETE FROM account WHERE id IN (SELECT id FROM email_duplicate);
+
+DROP TABLE email_duplicate;
@@ -0,0 +1,12 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+DROP INDEX account_realm_id_lower_email_index;
+
+CREATE UNIQUE INDEX account_realm_id_lower_email_key ON account (realm_id, LOWER(email));
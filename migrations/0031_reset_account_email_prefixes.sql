@@ -0,0 +1,13 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+-- Prefixes are now limited to 3 to 8 characters of the local part and hashed with a key derived
+-- from EMAIL_INDEX_KEY. The old rows are dropped and rebuilt by running rotate_email_keys.
+
+DELETE FROM account_email_prefix;
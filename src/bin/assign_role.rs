@@ -0,0 +1,68 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::env;
+use std::process;
+
+use auth_service::Error;
+use auth_service::dto::normalize_email;
+use auth_service::service::AccountService;
+
+use dotenv::dotenv;
+
+use env_logger::Env;
+
+#[actix_web::main]
+async fn main() -> Result<(), Error> {
+    dotenv().ok();
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+
+    let arguments = env::args().collect::<Vec<_>>();
+
+    let (email, role, realm) = match arguments.as_slice() {
+        [_, email, role] => (email, role, "default"),
+        [_, email, role, realm] => (email, role, realm.as_str()),
+        _ => {
+            log::error!("Usage: assign_role <email> <role> [realm]");
+            process::exit(1);
+        }
+    };
+
+    let service = AccountService::new();
+
+    let realm = match service.find_realm(Some(realm), None).await? {
+        Some(realm) => realm,
+        None => {
+            log::error!("No realm named {}", realm);
+            process::exit(1);
+        }
+    };
+
+    let mut transaction = service.transaction(&realm).await?;
+
+    let entity = match transaction.find_by_email(&normalize_email(email)).await? {
+        Some(entity) => entity,
+        None => {
+            log::error!("No account found for {}", email);
+            process::exit(1);
+        }
+    };
+
+    if !transaction.role_exists(role).await? {
+        log::error!("No role named {}", role);
+        process::exit(1);
+    }
+
+    transaction.assign_role(entity.id, role).await?;
+    transaction.commit().await?;
+
+    log::info!("Assigned role {} to {}", role, email);
+
+    Ok(())
+}
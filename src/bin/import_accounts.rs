@@ -0,0 +1,77 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::env;
+use std::fs;
+use std::process;
+
+use auth_service::Error;
+use auth_service::dto::ImportDto;
+use auth_service::extractor::ClientExtractor;
+use auth_service::service::AccountService;
+
+use dotenv::dotenv;
+
+use env_logger::Env;
+
+#[actix_web::main]
+async fn main() -> Result<(), Error> {
+    dotenv().ok();
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+
+    let arguments = env::args().collect::<Vec<_>>();
+    let dry_run = arguments.iter().any(|argument| argument == "--dry-run");
+    let arguments = arguments.into_iter().filter(|argument| argument != "--dry-run").collect::<Vec<_>>();
+
+    let (path, realm) = match arguments.as_slice() {
+        [_, path] => (path, "default"),
+        [_, path, realm] => (path, realm.as_str()),
+        _ => {
+            log::error!("Usage: import_accounts <file> [realm] [--dry-run]");
+            process::exit(1);
+        }
+    };
+
+    let input = match fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(error) => {
+            log::error!("Can't read {}: {}", path, error);
+            process::exit(1);
+        }
+    };
+
+    let rows = match path.ends_with(".csv") {
+        true => ImportDto::parse_csv(&input),
+        false => ImportDto::parse_json_lines(&input)
+    };
+
+    let service = AccountService::new();
+
+    let realm = match service.find_realm(Some(realm), None).await? {
+        Some(realm) => realm,
+        None => {
+            log::error!("No realm named {}", realm);
+            process::exit(1);
+        }
+    };
+
+    let client = ClientExtractor { ip_address: None, user_agent: Some("import_accounts".to_string()) };
+    let report = service.import(&realm, rows, dry_run, None, &client).await?;
+
+    for error in &report.errors {
+        log::warn!("Line {}: {}", error.line, error.message);
+    }
+
+    match dry_run {
+        true => log::info!("Dry run, {} accounts would be imported and {} lines were rejected", report.imported, report.errors.len()),
+        false => log::info!("Imported {} accounts, {} lines were rejected", report.imported, report.errors.len())
+    };
+
+    Ok(())
+}
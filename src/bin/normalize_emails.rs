@@ -0,0 +1,99 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::collections::HashSet;
+use std::env;
+use std::process;
+
+use auth_service::Error;
+use auth_service::service::AccountService;
+
+use dotenv::dotenv;
+
+use env_logger::Env;
+
+#[actix_web::main]
+async fn main() -> Result<(), Error> {
+    dotenv().ok();
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+
+    let arguments = env::args().collect::<Vec<_>>();
+
+    let merge = match arguments.as_slice() {
+        [_] => false,
+        [_, flag] if flag == "--merge" => true,
+        _ => {
+            log::error!("Usage: normalize_emails [--merge]");
+            process::exit(1);
+        }
+    };
+
+    let service = AccountService::new();
+    let mut total = 0;
+    let mut collisions = 0;
+    let mut merged = HashSet::new();
+
+    for realm in service.find_realms().await? {
+        let mut after = 0;
+
+        loop {
+            let mut transaction = service.transaction(&realm).await?;
+            let entities = transaction.find_accounts(after, 100).await?;
+
+            if entities.is_empty() {
+                break;
+            }
+
+            for entity in entities {
+                after = entity.id;
+
+                if merged.contains(&entity.id) {
+                    continue;
+                }
+
+                let existing = match transaction.normalize_email(&entity).await? {
+                    Some(existing) => existing,
+                    None => {
+                        total += 1;
+                        continue;
+                    }
+                };
+
+                if !merge {
+                    log::warn!("Account {} in realm {} collides with account {}", entity.public_id, realm.name, existing.public_id);
+                    collisions += 1;
+                    continue;
+                }
+
+                if existing.id < entity.id {
+                    transaction.merge_accounts(existing.id, entity.id).await?;
+                    log::info!("Merged account {} into account {} in realm {}", entity.public_id, existing.public_id, realm.name);
+                } else {
+                    transaction.merge_accounts(entity.id, existing.id).await?;
+                    merged.insert(existing.id);
+                    transaction.normalize_email(&entity).await?;
+                    log::info!("Merged account {} into account {} in realm {}", existing.public_id, entity.public_id, realm.name);
+                }
+
+                total += 1;
+            }
+
+            transaction.commit().await?;
+            log::info!("Normalized {} email addresses", total);
+        }
+    }
+
+    if collisions > 0 {
+        log::warn!("{} accounts collide with another account, run normalize_emails --merge to merge them into the oldest one", collisions);
+    } else {
+        log::info!("All email addresses are normalized");
+    }
+
+    Ok(())
+}
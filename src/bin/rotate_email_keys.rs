@@ -0,0 +1,44 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use auth_service::Error;
+use auth_service::service::AccountService;
+
+use dotenv::dotenv;
+
+use env_logger::Env;
+
+#[actix_web::main]
+async fn main() -> Result<(), Error> {
+    dotenv().ok();
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+
+    let service = AccountService::new();
+    let mut total = 0;
+
+    for realm in service.find_realms().await? {
+        loop {
+            let mut transaction = service.transaction(&realm).await?;
+            let count = transaction.rotate_email_keys(100).await?;
+
+            transaction.commit().await?;
+
+            if count == 0 {
+                break;
+            }
+
+            total += count;
+            log::info!("Re-encrypted {} email addresses", total);
+        }
+    }
+
+    log::info!("All email addresses are encrypted with the current key");
+
+    Ok(())
+}
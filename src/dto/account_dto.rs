@@ -7,25 +7,76 @@
  * permission of an Blackwood Studio Admin
  *******************************************************/
 
+use chrono::DateTime;
+use chrono::Utc;
+
 use serde::Deserialize;
 use serde::Serialize;
+
+use uuid::Uuid;
+
 use validator::Validate;
 
+use crate::dto::ProfileDto;
 use crate::entity::AccountEntity;
 
 #[derive(Validate, Deserialize, Serialize)]
 pub struct AccountDto {
+    #[serde(default)] 
+    pub id: Uuid,
+
     #[serde(default)] 
     #[validate(email(message = "Invalid email address"))]
     #[validate(length(min = 1, message = "The min size of the email is 1"))]
     #[validate(length(max = 255, message = "The max size of the email is 255"))]
-    pub email: String
+    pub email: String,
+
+    #[serde(default)] 
+    pub username: Option<String>,
+
+    #[serde(default)] 
+    pub roles: Vec<String>,
+
+    #[serde(default)] 
+    pub organization_id: Option<i32>,
+
+    #[serde(default)] 
+    pub impersonator_id: Option<Uuid>,
+
+    #[serde(default)] 
+    pub profile: ProfileDto,
+
+    #[serde(default)] 
+    pub created_at: DateTime<Utc>,
+
+    #[serde(default)] 
+    pub updated_at: DateTime<Utc>,
+
+    #[serde(default)] 
+    pub last_login_at: Option<DateTime<Utc>>,
+
+    #[serde(default)] 
+    pub last_seen_at: Option<DateTime<Utc>>,
+
+    #[serde(default)] 
+    pub password_changed_at: Option<DateTime<Utc>>
 }
 
 impl From<AccountEntity> for AccountDto {
     fn from(entity: AccountEntity) -> AccountDto {
         AccountDto {  
-            email: entity.email
+            id: entity.public_id,
+            profile: ProfileDto::from(&entity),
+            email: entity.email,
+            username: entity.username,
+            roles: Vec::new(),
+            organization_id: None,
+            impersonator_id: None,
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+            last_login_at: entity.last_login_at,
+            last_seen_at: entity.last_seen_at,
+            password_changed_at: entity.password_changed_at
         }
     }
 }
@@ -0,0 +1,45 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use uuid::Uuid;
+
+use validator::Validate;
+
+use crate::dto::email_dto::deserialize_optional_email;
+use crate::entity::AccountStatus;
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct AccountQueryDto {
+    #[serde(default)] 
+    #[validate(length(min = 3, message = "The min size of the email is 3"))]
+    #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_optional_email")]
+    pub email: Option<String>,
+
+    #[serde(default)] 
+    #[validate(length(max = 64, message = "The max size of the username is 64"))]
+    pub username: Option<String>,
+
+    #[serde(default)] 
+    pub status: Option<AccountStatus>,
+
+    #[serde(default)] 
+    pub after: Option<Uuid>,
+
+    #[serde(default = "default_limit")] 
+    #[validate(range(min = 1, max = 100, message = "The limit has to be between 1 and 100"))]
+    pub limit: i64
+}
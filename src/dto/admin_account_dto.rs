@@ -0,0 +1,46 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dto::AccountDto;
+use crate::dto::SessionDto;
+use crate::entity::AccountEntity;
+use crate::entity::AccountStatus;
+
+#[derive(Deserialize, Serialize)]
+pub struct AdminAccountDto {
+    #[serde(flatten)]
+    pub account: AccountDto,
+
+    pub status: AccountStatus,
+    pub suspended_reason: Option<String>,
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub deletion_requested_at: Option<DateTime<Utc>>,
+
+    #[serde(default)] 
+    pub sessions: Vec<SessionDto>
+}
+
+impl From<AccountEntity> for AdminAccountDto {
+    fn from(entity: AccountEntity) -> AdminAccountDto {
+        AdminAccountDto {
+            status: entity.status,
+            suspended_reason: entity.suspended_reason.clone(),
+            suspended_until: entity.suspended_until,
+            deletion_requested_at: entity.deletion_requested_at,
+            sessions: Vec::new(),
+            account: AccountDto::from(entity)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::entity::AuditAction;
+use crate::entity::AuditEventEntity;
+use crate::entity::AuditOutcome;
+
+#[derive(Deserialize, Serialize)]
+pub struct AuditEventDto {
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>
+}
+
+impl From<AuditEventEntity> for AuditEventDto {
+    fn from(entity: AuditEventEntity) -> AuditEventDto {
+        AuditEventDto {
+            action: entity.action,
+            outcome: entity.outcome,
+            ip_address: entity.ip_address,
+            user_agent: entity.user_agent,
+            created_at: entity.created_at
+        }
+    }
+}
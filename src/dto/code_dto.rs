@@ -0,0 +1,24 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct CodeDto {
+    #[serde(default)] 
+    #[validate(length(equal = 6, message = "The size of the code is 6"))]
+    pub code: String,
+
+    #[serde(default)] 
+    #[validate(length(min = 8, message = "The min size of the new password is 8"))]
+    #[validate(length(max = 255, message = "The max size of the new password is 255"))]
+    pub new_password: Option<String>
+}
@@ -0,0 +1,35 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::entity::DeviceEntity;
+
+#[derive(Deserialize, Serialize)]
+pub struct DeviceDto {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>
+}
+
+impl From<DeviceEntity> for DeviceDto {
+    fn from(entity: DeviceEntity) -> DeviceDto {
+        DeviceDto {
+            ip_address: entity.ip_address,
+            user_agent: entity.user_agent,
+            created_at: entity.created_at,
+            last_seen_at: entity.last_seen_at
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct EmailDto {
+    #[serde(default)] 
+    #[validate(email(message = "Invalid email address"))]
+    #[validate(length(min = 1, message = "The min size of the email is 1"))]
+    #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_email")]
+    pub email: String
+}
+
+pub fn normalize_email(email: &str) -> String {
+    let email = email.trim();
+
+    match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local, domain.to_lowercase()),
+        None => email.to_string()
+    }
+}
+
+pub(crate) fn deserialize_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(normalize_email(&String::deserialize(deserializer)?))
+}
+
+pub(crate) fn deserialize_optional_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|email| normalize_email(&email)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_email_lowercases_only_the_domain() {
+        assert_eq!(normalize_email(" Bob@Example.COM "), "Bob@example.com");
+        assert_eq!(normalize_email("first.last+news@Gmail.com"), "first.last+news@gmail.com");
+        assert_eq!(normalize_email("a@b@Example.com"), "a@b@example.com");
+        assert_eq!(normalize_email(" no-at-sign "), "no-at-sign");
+    }
+}
@@ -0,0 +1,36 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::entity::ExportEntity;
+use crate::entity::ExportStatus;
+
+#[derive(Deserialize, Serialize)]
+pub struct ExportDto {
+    pub id: i32,
+    pub status: ExportStatus,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>
+}
+
+impl From<ExportEntity> for ExportDto {
+    fn from(entity: ExportEntity) -> ExportDto {
+        ExportDto {
+            id: entity.id,
+            status: entity.status,
+            created_at: entity.created_at,
+            completed_at: entity.completed_at
+        }
+    }
+}
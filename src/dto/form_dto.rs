@@ -11,16 +11,28 @@ use serde::Deserialize;
 use serde::Serialize;
 use validator::Validate;
 
+use crate::dto::email_dto::deserialize_email;
+
 #[derive(Validate, Deserialize, Serialize)]
 pub struct FormDto {
     #[serde(default)] 
-    #[validate(email(message = "Invalid email address"))]
     #[validate(length(min = 1, message = "The min size of the email is 1"))]
     #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_email")]
     pub email: String,
 
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the username is 1"))]
+    #[validate(length(max = 64, message = "The max size of the username is 64"))]
+    pub username: Option<String>,
+
     #[serde(default)] 
     #[validate(length(min = 8, message = "The min size of the password is 8"))]
     #[validate(length(max = 255, message = "The max size of the password is 255"))]
-    pub password: String
+    pub password: String,
+
+    #[serde(default)] 
+    #[validate(length(min = 8, message = "The min size of the new password is 8"))]
+    #[validate(length(max = 255, message = "The max size of the new password is 255"))]
+    pub new_password: Option<String>
 }
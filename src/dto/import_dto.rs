@@ -0,0 +1,66 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+use crate::dto::email_dto::deserialize_email;
+use crate::entity::PasswordAlgorithm;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct ImportDto {
+    #[serde(default)] 
+    #[validate(email(message = "Invalid email address"))]
+    #[validate(length(min = 1, message = "The min size of the email is 1"))]
+    #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_email")]
+    pub email: String,
+
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the password hash is 1"))]
+    #[validate(length(max = 255, message = "The max size of the password hash is 255"))]
+    pub password_hash: String,
+
+    #[serde(default)] 
+    pub algorithm: Option<PasswordAlgorithm>
+}
+
+impl ImportDto {
+    pub fn parse_csv(input: &str) -> Vec<(u64, Result<ImportDto, String>)> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input.as_bytes());
+
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(error) => {
+                return vec![(1, Err(error.to_string()))];
+            }
+        };
+
+        reader.records().map(|record| match record {
+            Ok(record) => (
+                record.position().map(|position| position.line()).unwrap_or_default(),
+                record.deserialize::<ImportDto>(Some(&headers)).map_err(|error| error.to_string())
+            ),
+            Err(error) => (
+                error.position().map(|position| position.line()).unwrap_or_default(),
+                Err(error.to_string())
+            )
+        })
+        .collect()
+    }
+
+    pub fn parse_json_lines(input: &str) -> Vec<(u64, Result<ImportDto, String>)> {
+        input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index as u64 + 1, serde_json::from_str::<ImportDto>(line).map_err(|error| error.to_string())))
+        .collect()
+    }
+}
@@ -0,0 +1,26 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    Csv,
+    JsonLines
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ImportQueryDto {
+    pub format: ImportFormat,
+
+    #[serde(default)] 
+    pub dry_run: bool
+}
@@ -0,0 +1,24 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Deserialize, Serialize)]
+pub struct ImportErrorDto {
+    pub line: u64,
+    pub message: String
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ImportReportDto {
+    pub dry_run: bool,
+    pub imported: usize,
+    pub errors: Vec<ImportErrorDto>
+}
@@ -0,0 +1,27 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+use crate::dto::email_dto::deserialize_email;
+use crate::entity::OrganizationRole;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct InvitationDto {
+    #[serde(default)] 
+    #[validate(email(message = "Invalid email address"))]
+    #[validate(length(min = 1, message = "The min size of the email is 1"))]
+    #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_email")]
+    pub email: String,
+
+    pub role: OrganizationRole
+}
@@ -0,0 +1,33 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use uuid::Uuid;
+
+use crate::entity::MembershipEntity;
+use crate::entity::OrganizationRole;
+
+#[derive(Deserialize, Serialize)]
+pub struct MembershipDto {
+    pub account_id: Uuid,
+    pub email: String,
+    pub role: OrganizationRole
+}
+
+impl From<MembershipEntity> for MembershipDto {
+    fn from(entity: MembershipEntity) -> MembershipDto {
+        MembershipDto {
+            account_id: entity.account.public_id,
+            email: entity.account.email,
+            role: entity.role
+        }
+    }
+}
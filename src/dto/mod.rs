@@ -10,8 +10,77 @@
 mod account_dto;
 pub use account_dto::AccountDto;
 
+mod account_query_dto;
+pub use account_query_dto::AccountQueryDto;
+
+mod admin_account_dto;
+pub use admin_account_dto::AdminAccountDto;
+
+mod audit_event_dto;
+pub use audit_event_dto::AuditEventDto;
+
+mod code_dto;
+pub use code_dto::CodeDto;
+
+mod device_dto;
+pub use device_dto::DeviceDto;
+
+mod email_dto;
+pub use email_dto::EmailDto;
+pub use email_dto::normalize_email;
+
+mod error_dto;
+pub use error_dto::ErrorDto;
+
+mod export_dto;
+pub use export_dto::ExportDto;
+
 mod form_dto;
 pub use form_dto::FormDto;
 
+mod import_dto;
+pub use import_dto::ImportDto;
+
+mod import_query_dto;
+pub use import_query_dto::ImportFormat;
+pub use import_query_dto::ImportQueryDto;
+
+mod import_report_dto;
+pub use import_report_dto::ImportErrorDto;
+pub use import_report_dto::ImportReportDto;
+
+mod invitation_dto;
+pub use invitation_dto::InvitationDto;
+
+mod membership_dto;
+pub use membership_dto::MembershipDto;
+
+mod organization_dto;
+pub use organization_dto::OrganizationDto;
+
+mod organization_role_dto;
+pub use organization_role_dto::OrganizationRoleDto;
+
+mod password_dto;
+pub use password_dto::PasswordDto;
+
+mod profile_dto;
+pub use profile_dto::ProfileDto;
+
+mod role_dto;
+pub use role_dto::RoleDto;
+
+mod session_dto;
+pub use session_dto::SessionDto;
+
+mod suspension_dto;
+pub use suspension_dto::SuspensionDto;
+
+mod token_dto;
+pub use token_dto::TokenDto;
+
 mod update_dto;
 pub use update_dto::UpdateDto;
+
+mod username_dto;
+pub use username_dto::UsernameDto;
@@ -0,0 +1,39 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+use crate::entity::OrganizationEntity;
+use crate::entity::OrganizationRole;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct OrganizationDto {
+    #[serde(default)] 
+    pub id: i32,
+
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the name is 1"))]
+    #[validate(length(max = 255, message = "The max size of the name is 255"))]
+    pub name: String,
+
+    #[serde(default)] 
+    pub role: Option<OrganizationRole>
+}
+
+impl From<OrganizationEntity> for OrganizationDto {
+    fn from(entity: OrganizationEntity) -> OrganizationDto {
+        OrganizationDto {
+            id: entity.id,
+            name: entity.name,
+            role: Some(entity.role)
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::entity::OrganizationRole;
+
+#[derive(Deserialize, Serialize)]
+pub struct OrganizationRoleDto {
+    pub role: OrganizationRole
+}
@@ -0,0 +1,96 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono_tz::Tz;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use serde_json::Map;
+use serde_json::Value;
+
+use validator::Validate;
+use validator::ValidationError;
+
+use crate::entity::AccountEntity;
+
+fn validation_error(code: &'static str, message: &'static str) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
+}
+
+fn validate_locale(locale: &str) -> Result<(), ValidationError> {
+    let valid = locale.split('-').all(|subtag| (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    match valid {
+        true => Ok(()),
+        false => Err(validation_error("locale", "Invalid locale"))
+    }
+}
+
+fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
+    match timezone.parse::<Tz>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(validation_error("timezone", "Invalid timezone"))
+    }
+}
+
+fn default_attributes() -> Value {
+    Value::Object(Map::new())
+}
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct ProfileDto {
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the display name is 1"))]
+    #[validate(length(max = 255, message = "The max size of the display name is 255"))]
+    pub display_name: Option<String>,
+
+    #[serde(default)] 
+    #[validate(url(message = "Invalid avatar url"))]
+    #[validate(length(max = 2048, message = "The max size of the avatar url is 2048"))]
+    pub avatar_url: Option<String>,
+
+    #[serde(default)] 
+    #[validate(custom = "validate_locale")]
+    #[validate(length(max = 35, message = "The max size of the locale is 35"))]
+    pub locale: Option<String>,
+
+    #[serde(default)] 
+    #[validate(custom = "validate_timezone")]
+    pub timezone: Option<String>,
+
+    #[serde(default = "default_attributes")] 
+    pub attributes: Value
+}
+
+impl Default for ProfileDto {
+    fn default() -> ProfileDto {
+        ProfileDto {
+            display_name: None,
+            avatar_url: None,
+            locale: None,
+            timezone: None,
+            attributes: default_attributes()
+        }
+    }
+}
+
+impl From<&AccountEntity> for ProfileDto {
+    fn from(entity: &AccountEntity) -> ProfileDto {
+        ProfileDto {
+            display_name: entity.display_name.clone(),
+            avatar_url: entity.avatar_url.clone(),
+            locale: entity.locale.clone(),
+            timezone: entity.timezone.clone(),
+            attributes: entity.attributes.clone()
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::entity::SessionEntity;
+
+#[derive(Deserialize, Serialize)]
+pub struct SessionDto {
+    pub id: i32,
+    pub organization_id: Option<i32>,
+    pub elevated: bool,
+    pub impersonated: bool
+}
+
+impl From<SessionEntity> for SessionDto {
+    fn from(entity: SessionEntity) -> SessionDto {
+        SessionDto {
+            id: entity.id,
+            organization_id: entity.organization_id,
+            elevated: entity.elevated,
+            impersonated: entity.impersonator_id.is_some()
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct SuspensionDto {
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the reason is 1"))]
+    #[validate(length(max = 255, message = "The max size of the reason is 255"))]
+    pub reason: Option<String>,
+
+    #[serde(default)] 
+    pub until: Option<DateTime<Utc>>
+}
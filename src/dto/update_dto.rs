@@ -11,16 +11,24 @@ use serde::Deserialize;
 use serde::Serialize;
 use validator::Validate;
 
+use crate::dto::email_dto::deserialize_optional_email;
+
 #[derive(Validate, Deserialize, Serialize)]
 pub struct UpdateDto {
     #[serde(default)] 
     #[validate(email(message = "Invalid email address"))]
     #[validate(length(min = 1, message = "The min size of the email is 1"))]
     #[validate(length(max = 255, message = "The max size of the email is 255"))]
+    #[serde(deserialize_with = "deserialize_optional_email")]
     pub email: Option<String>,
 
     #[serde(default)] 
     #[validate(length(min = 8, message = "The min size of the password is 8"))]
     #[validate(length(max = 255, message = "The max size of the password is 255"))]
-    pub password: Option<String>
+    pub password: Option<String>,
+
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the current password is 1"))]
+    #[validate(length(max = 255, message = "The max size of the current password is 255"))]
+    pub current_password: Option<String>
 }
@@ -0,0 +1,20 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, Serialize)]
+pub struct UsernameDto {
+    #[serde(default)] 
+    #[validate(length(min = 1, message = "The min size of the username is 1"))]
+    #[validate(length(max = 64, message = "The max size of the username is 64"))]
+    pub username: String
+}
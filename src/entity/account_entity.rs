@@ -7,13 +7,72 @@
  * permission of an Blackwood Studio Admin
  *******************************************************/
 
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use serde_json::Value;
+
 use sqlx::FromRow;
+use sqlx::Type;
+
+use uuid::Uuid;
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStatus {
+    Active,
+    Unverified,
+    Suspended,
+    PendingDeletion
+}
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordAlgorithm {
+    Bcrypt,
+    Argon2,
+    Scrypt,
+    Pbkdf2,
+    SaltedSha1,
+    SaltedSha256,
+    SaltedSha512
+}
 
 #[derive(FromRow)]
 pub struct AccountEntity {
     pub id: i32,
+    pub public_id: Uuid,
+    #[sqlx(skip)]
     pub email: String,
+    #[sqlx(rename = "email")]
+    pub legacy_email: Option<String>,
+    pub email_ciphertext: Option<Vec<u8>>,
+    pub email_key_id: Option<String>,
+    pub username: Option<String>,
+    pub username_changed_at: Option<DateTime<Utc>>,
     pub password_hash: String,
-    pub write_key: String,
-    pub read_key: String
+    pub password_pepper_id: Option<String>,
+    pub password_algorithm: PasswordAlgorithm,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub attributes: Value,
+    pub status: AccountStatus,
+    pub suspended_reason: Option<String>,
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub deletion_requested_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub password_change_required: bool,
+    #[sqlx(default)]
+    pub impersonator_id: Option<i32>
 }
@@ -0,0 +1,78 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use sqlx::FromRow;
+use sqlx::Type;
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Register,
+    Login,
+    LoginLink,
+    LoginCode,
+    Logout,
+    Reauthenticate,
+    RevokeSession,
+    Update,
+    UpdateProfile,
+    UpdateUsername,
+    ConfirmEmail,
+    UndoEmail,
+    Delete,
+    Restore,
+    Purge,
+    AdminDelete,
+    Verify,
+    Suspend,
+    Unsuspend,
+    Export,
+    ListAccounts,
+    ViewAccount,
+    ResetPassword,
+    RevokeCredentials,
+    Impersonate,
+    Import,
+    AssignRole,
+    RevokeRole,
+    CreateOrganization,
+    UpdateOrganization,
+    DeleteOrganization,
+    Invite,
+    AcceptInvitation,
+    DeclineInvitation,
+    UpdateMembership,
+    RemoveMembership
+}
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure
+}
+
+#[derive(FromRow)]
+pub struct AuditEventEntity {
+    pub id: i32,
+    pub account_id: Option<i32>,
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>
+}
@@ -0,0 +1,25 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use sqlx::FromRow;
+
+#[derive(FromRow)]
+pub struct EmailChangeEntity {
+    pub id: i32,
+    pub account_id: i32,
+    #[sqlx(skip)]
+    pub old_email: String,
+    #[sqlx(skip)]
+    pub new_email: String,
+    pub old_email_ciphertext: Vec<u8>,
+    pub new_email_ciphertext: Vec<u8>,
+    pub email_key_id: String,
+    pub confirm_token: String,
+    pub undo_token: String
+}
@@ -0,0 +1,35 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use sqlx::FromRow;
+use sqlx::Type;
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Ready,
+    Failed
+}
+
+#[derive(FromRow)]
+pub struct ExportEntity {
+    pub id: i32,
+    pub account_id: i32,
+    pub status: ExportStatus,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>
+}
@@ -0,0 +1,24 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use sqlx::FromRow;
+
+use crate::entity::OrganizationRole;
+
+#[derive(FromRow)]
+pub struct InvitationEntity {
+    pub id: i32,
+    pub organization_id: i32,
+    pub organization_name: String,
+    #[sqlx(skip)]
+    pub email: String,
+    pub email_ciphertext: Vec<u8>,
+    pub email_key_id: String,
+    pub role: OrganizationRole
+}
@@ -0,0 +1,18 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use sqlx::FromRow;
+
+#[derive(FromRow)]
+pub struct LoginCodeEntity {
+    pub id: i32,
+    pub account_id: i32,
+    pub code_hash: String,
+    pub attempts: i32
+}
@@ -0,0 +1,20 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use sqlx::FromRow;
+
+use crate::entity::AccountEntity;
+use crate::entity::OrganizationRole;
+
+#[derive(FromRow)]
+pub struct MembershipEntity {
+    #[sqlx(flatten)]
+    pub account: AccountEntity,
+    pub role: OrganizationRole
+}
@@ -9,3 +9,42 @@
 
 mod account_entity;
 pub use account_entity::AccountEntity;
+pub use account_entity::AccountStatus;
+pub use account_entity::PasswordAlgorithm;
+
+mod audit_event_entity;
+pub use audit_event_entity::AuditAction;
+pub use audit_event_entity::AuditEventEntity;
+pub use audit_event_entity::AuditOutcome;
+
+mod device_entity;
+pub use device_entity::DeviceEntity;
+
+mod email_change_entity;
+pub use email_change_entity::EmailChangeEntity;
+
+mod export_entity;
+pub use export_entity::ExportEntity;
+pub use export_entity::ExportStatus;
+
+mod invitation_entity;
+pub use invitation_entity::InvitationEntity;
+
+mod login_code_entity;
+pub use login_code_entity::LoginCodeEntity;
+
+mod membership_entity;
+pub use membership_entity::MembershipEntity;
+
+mod organization_entity;
+pub use organization_entity::OrganizationEntity;
+pub use organization_entity::OrganizationRole;
+
+mod password_history_entity;
+pub use password_history_entity::PasswordHistoryEntity;
+
+mod realm_entity;
+pub use realm_entity::RealmEntity;
+
+mod session_entity;
+pub use session_entity::SessionEntity;
@@ -0,0 +1,30 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use sqlx::FromRow;
+use sqlx::Type;
+
+#[derive(Type, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizationRole {
+    Member,
+    Admin,
+    Owner
+}
+
+#[derive(FromRow)]
+pub struct OrganizationEntity {
+    pub id: i32,
+    pub name: String,
+    pub role: OrganizationRole
+}
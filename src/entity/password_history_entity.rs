@@ -0,0 +1,25 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use sqlx::FromRow;
+
+use crate::entity::PasswordAlgorithm;
+
+#[derive(FromRow)]
+pub struct PasswordHistoryEntity {
+    pub id: i32,
+    pub account_id: i32,
+    pub password_hash: String,
+    pub password_pepper_id: Option<String>,
+    pub password_algorithm: PasswordAlgorithm,
+    pub created_at: DateTime<Utc>
+}
@@ -0,0 +1,28 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use serde_json::Value;
+
+use sqlx::FromRow;
+
+#[derive(FromRow, Clone)]
+pub struct RealmEntity {
+    pub id: i32,
+    pub name: String,
+    pub hostname: Option<String>,
+    pub registration_open: bool,
+    pub password_min_length: i32,
+    pub cookie_domain: Option<String>,
+    pub profile_schema: Value,
+    pub email_verification: bool,
+    pub username_pattern: String,
+    pub reserved_usernames: Vec<String>,
+    pub password_history_depth: i32,
+    pub password_max_age: Option<i32>
+}
@@ -0,0 +1,22 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use sqlx::FromRow;
+
+#[derive(FromRow)]
+pub struct SessionEntity {
+    pub id: i32,
+    pub account_id: i32,
+    pub write_key: String,
+    pub read_key: String,
+    pub revoke_token: String,
+    pub organization_id: Option<i32>,
+    pub impersonator_id: Option<i32>,
+    pub elevated: bool
+}
@@ -11,6 +11,8 @@ use actix_web::ResponseError;
 use actix_web::HttpResponse;
 use actix_web::http::StatusCode;
 
+use crate::dto::ErrorDto;
+
 #[derive(Debug, derive_more::Display, derive_more::Error)]
 pub enum Error {
     ValidationError (validator::ValidationErrors),
@@ -19,13 +21,58 @@ pub enum Error {
     DatabaseError,
     
     #[display(fmt = "During the hashing process something went wrong")]
-    HashingError
+    HashingError,
+
+    #[display(fmt = "During the encryption process something went wrong")]
+    EncryptionError,
+
+    #[display(fmt = "Sending an email has failed")]
+    MailError,
+
+    #[display(fmt = "This action requires a recent re-authentication")]
+    ReauthRequired,
+
+    #[display(fmt = "{}", _0)]
+    Forbidden(#[error(not(source))] &'static str),
+
+    #[display(fmt = "Unknown realm")]
+    RealmNotFound,
+
+    #[display(fmt = "The profile schema of this realm is invalid")]
+    ProfileSchemaError,
+
+    #[display(fmt = "The username pattern of this realm is invalid")]
+    UsernamePatternError,
+
+    #[display(fmt = "The password has expired and has to be changed")]
+    PasswordExpired,
+
+    #[display(fmt = "{}", _0)]
+    InvalidPassword(#[error(not(source))] String),
+
+    #[display(fmt = "{}", _0)]
+    AccountSuspended(#[error(not(source))] String),
+
+    #[display(fmt = "Creating the data export has failed")]
+    ExportError
 }
 
 impl ResponseError for Error {
     fn error_response(&self) -> HttpResponse {
         match self {
             Error::ValidationError(errors) => HttpResponse::build(self.status_code()).body(errors.to_string()),
+            Error::ReauthRequired => HttpResponse::build(self.status_code()).json(ErrorDto {
+                error: "reauth_required".to_string(),
+                message: self.to_string()
+            }),
+            Error::PasswordExpired => HttpResponse::build(self.status_code()).json(ErrorDto {
+                error: "password_expired".to_string(),
+                message: self.to_string()
+            }),
+            Error::AccountSuspended(_) => HttpResponse::build(self.status_code()).json(ErrorDto {
+                error: "account_suspended".to_string(),
+                message: self.to_string()
+            }),
             _ => HttpResponse::build(self.status_code()).body(self.to_string())
         }
     }
@@ -33,6 +80,12 @@ impl ResponseError for Error {
     fn status_code(&self) -> StatusCode {
         match self {
             Error::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Error::InvalidPassword(_) => StatusCode::BAD_REQUEST,
+            Error::ReauthRequired => StatusCode::FORBIDDEN,
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::RealmNotFound => StatusCode::NOT_FOUND,
+            Error::PasswordExpired => StatusCode::FORBIDDEN,
+            Error::AccountSuspended(_) => StatusCode::FORBIDDEN,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -55,3 +108,39 @@ impl From<bcrypt::BcryptError> for Error {
         Error::HashingError
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(_: serde_json::Error) -> Error {
+        Error::ExportError
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(_: zip::result::ZipError) -> Error {
+        Error::ExportError
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(_: std::io::Error) -> Error {
+        Error::ExportError
+    }
+}
+
+impl From<lettre::error::Error> for Error {
+    fn from(_: lettre::error::Error) -> Error {
+        Error::MailError
+    }
+}
+
+impl From<lettre::address::AddressError> for Error {
+    fn from(_: lettre::address::AddressError) -> Error {
+        Error::MailError
+    }
+}
+
+impl From<lettre::transport::smtp::Error> for Error {
+    fn from(_: lettre::transport::smtp::Error) -> Error {
+        Error::MailError
+    }
+}
@@ -0,0 +1,74 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::FromRequest;
+use actix_web::HttpRequest;
+use actix_web::dev::Payload;
+use actix_web::http::Method;
+use actix_web::web::Data;
+
+use crate::Error;
+use crate::entity::AccountEntity;
+use crate::extractor::RealmExtractor;
+use crate::service::AccountService;
+
+pub struct AuthorizationExtractor {
+    pub entity: AccountEntity,
+    pub permissions: Vec<String>
+}
+
+impl AuthorizationExtractor {
+    pub fn require(&self, permission: &str) -> Result<(), Error> {
+        match self.permissions.iter().any(|granted| granted == permission) {
+            true => Ok(()),
+            false => Err(Error::Forbidden("Missing permission"))
+        }
+    }
+}
+
+impl FromRequest for AuthorizationExtractor {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<AuthorizationExtractor, Error>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let service = request.app_data::<Data<AccountService>>().cloned();
+        let realm = RealmExtractor::from_request(request, payload);
+        let safe = matches!(*request.method(), Method::GET | Method::HEAD);
+        let key = match safe {
+            true => request.cookie("READ_KEY"),
+            false => request.cookie("WRITE_KEY")
+        }.map(|key_cookie| key_cookie.value().to_string());
+
+        Box::pin(async move {
+            let service = service.ok_or(Error::DatabaseError)?;
+            let key = key.ok_or(Error::Forbidden("No key provided"))?;
+            let realm = realm.await?;
+
+            let mut transaction = service.transaction(&realm.entity).await?;
+
+            let entity = match safe {
+                true => transaction.find_by_read_key(&key).await?,
+                false => transaction.find_by_write_key(&key).await?
+            }.ok_or(Error::Forbidden("Invalid key provided"))?;
+
+            if !safe && entity.impersonator_id.is_some() {
+                return Err(Error::Forbidden("This action is not available while impersonating"));
+            }
+
+            let permissions = transaction.find_permissions(entity.id).await?;
+
+            transaction.commit().await?;
+
+            Ok(AuthorizationExtractor { entity, permissions })
+        })
+    }
+}
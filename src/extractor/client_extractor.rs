@@ -0,0 +1,73 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::future::Ready;
+use std::future::ready;
+use std::net::IpAddr;
+
+use actix_web::FromRequest;
+use actix_web::HttpRequest;
+use actix_web::dev::Payload;
+use actix_web::http::header::USER_AGENT;
+
+use dotenv_codegen::dotenv;
+
+pub struct ClientExtractor {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>
+}
+
+impl ClientExtractor {
+    fn ip_address(request: &HttpRequest) -> Option<IpAddr> {
+        let trusted_proxies = dotenv!("TRUSTED_PROXIES")
+        .split(',')
+        .filter_map(|proxy| proxy.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+
+        let peer_address = request.peer_addr()?.ip();
+
+        if !trusted_proxies.contains(&peer_address) {
+            return Some(peer_address);
+        }
+
+        let forwarded_for = request.headers().get_all("X-Forwarded-For")
+        .filter_map(|forwarded_for| forwarded_for.to_str().ok())
+        .flat_map(|forwarded_for| forwarded_for.split(','))
+        .map(|ip_address| ip_address.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+
+        for ip_address in forwarded_for.into_iter().rev() {
+            match ip_address {
+                Some(ip_address) if trusted_proxies.contains(&ip_address) => continue,
+                Some(ip_address) => {
+                    return Some(ip_address);
+                },
+                None => {
+                    return None;
+                }
+            }
+        }
+
+        Some(peer_address)
+    }
+}
+
+impl FromRequest for ClientExtractor {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<ClientExtractor, actix_web::Error>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let ip_address = ClientExtractor::ip_address(request).map(|ip_address| ip_address.to_string());
+        let user_agent = request.headers().get(USER_AGENT)
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .map(|user_agent| user_agent.chars().take(1024).collect());
+
+        ready(Ok(ClientExtractor { ip_address, user_agent }))
+    }
+}
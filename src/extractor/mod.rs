@@ -0,0 +1,17 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+mod authorization_extractor;
+pub use authorization_extractor::AuthorizationExtractor;
+
+mod client_extractor;
+pub use client_extractor::ClientExtractor;
+
+mod realm_extractor;
+pub use realm_extractor::RealmExtractor;
@@ -0,0 +1,115 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::FromRequest;
+use actix_web::HttpMessage;
+use actix_web::HttpRequest;
+use actix_web::cookie::Cookie;
+use actix_web::cookie::CookieBuilder;
+use actix_web::dev::Payload;
+use actix_web::web::Data;
+
+use chrono::Duration;
+use chrono::Utc;
+
+use jsonschema::JSONSchema;
+
+use regex::Regex;
+
+use serde_json::Value;
+
+use crate::Error;
+use crate::entity::AccountEntity;
+use crate::entity::RealmEntity;
+use crate::middleware::RealmPrefix;
+use crate::service::AccountService;
+
+pub struct RealmExtractor {
+    pub entity: RealmEntity,
+    pub prefix: String
+}
+
+impl RealmExtractor {
+    pub fn accepts_password(&self, password: &str) -> bool {
+        password.chars().count() >= self.entity.password_min_length as usize
+    }
+
+    pub fn password_expired(&self, entity: &AccountEntity) -> bool {
+        if entity.password_change_required {
+            return true;
+        }
+
+        match self.entity.password_max_age {
+            Some(password_max_age) => entity.password_changed_at.unwrap_or(entity.created_at) + Duration::seconds(password_max_age as i64) < Utc::now(),
+            None => false
+        }
+    }
+
+    pub fn attribute_errors(&self, attributes: &Value) -> Result<Vec<String>, Error> {
+        let schema = JSONSchema::compile(&self.entity.profile_schema).map_err(|_| Error::ProfileSchemaError)?;
+
+        let errors = match schema.validate(attributes) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.map(|error| error.to_string()).collect()
+        };
+
+        Ok(errors)
+    }
+
+    pub fn username_error(&self, username: &str) -> Result<Option<&'static str>, Error> {
+        let pattern = Regex::new(&self.entity.username_pattern).map_err(|_| Error::UsernamePatternError)?;
+
+        if username.contains('@') || !pattern.is_match(username) {
+            return Ok(Some("Invalid username"));
+        }
+
+        if self.entity.reserved_usernames.iter().any(|reserved| reserved.eq_ignore_ascii_case(username)) {
+            return Ok(Some("This username is reserved"));
+        }
+
+        Ok(None)
+    }
+
+    pub fn cookie(&self, name: &'static str, value: &str, path: &str) -> CookieBuilder<'static> {
+        let builder = Cookie::build(name, value.to_string()).path(format!("{}{}", self.prefix, path));
+
+        match &self.entity.cookie_domain {
+            Some(cookie_domain) => builder.domain(cookie_domain.clone()),
+            None => builder
+        }
+    }
+}
+
+impl FromRequest for RealmExtractor {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<RealmExtractor, Error>>>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let service = request.app_data::<Data<AccountService>>().cloned();
+        let name = request.extensions().get::<RealmPrefix>().map(|prefix| prefix.0.clone());
+        let hostname = request.connection_info().host().split(':').next().map(|hostname| hostname.to_string());
+
+        Box::pin(async move {
+            let service = service.ok_or(Error::DatabaseError)?;
+
+            let entity = service.find_realm(name.as_deref(), hostname.as_deref()).await?
+            .ok_or(Error::RealmNotFound)?;
+
+            let prefix = match name {
+                Some(name) => format!("/realms/{}", name),
+                None => String::new()
+            };
+
+            Ok(RealmExtractor { entity, prefix })
+        })
+    }
+}
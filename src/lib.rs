@@ -12,5 +12,7 @@ pub use error::Error;
 
 pub mod dto;
 pub mod entity;
+pub mod extractor;
+pub mod middleware;
 pub mod service;
 pub mod transaction;
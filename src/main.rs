@@ -8,13 +8,15 @@
  *******************************************************/
 
 use std::io;
+use std::io::Cursor;
+use std::io::Write;
+use std::time::Duration;
 
 use actix_web::App;
 use actix_web::HttpRequest;
 use actix_web::HttpResponse;
 use actix_web::HttpServer;
 use actix_web::Responder;
-use actix_web::cookie::Cookie;
 use actix_web::delete;
 use actix_web::get;
 use actix_web::middleware::Logger;
@@ -22,21 +24,284 @@ use actix_web::post;
 use actix_web::put;
 use actix_web::web::Data;
 use actix_web::web::Json;
+use actix_web::web::Path;
+use actix_web::web::Query;
 
 use auth_service::Error;
 use auth_service::dto::AccountDto;
+use auth_service::dto::AccountQueryDto;
+use auth_service::dto::AdminAccountDto;
+use auth_service::dto::AuditEventDto;
+use auth_service::dto::CodeDto;
+use auth_service::dto::DeviceDto;
+use auth_service::dto::EmailDto;
+use auth_service::dto::ExportDto;
 use auth_service::dto::FormDto;
+use auth_service::dto::ImportDto;
+use auth_service::dto::ImportFormat;
+use auth_service::dto::ImportQueryDto;
+use auth_service::dto::InvitationDto;
+use auth_service::dto::MembershipDto;
+use auth_service::dto::OrganizationDto;
+use auth_service::dto::OrganizationRoleDto;
+use auth_service::dto::PasswordDto;
+use auth_service::dto::ProfileDto;
+use auth_service::dto::RoleDto;
+use auth_service::dto::SessionDto;
+use auth_service::dto::SuspensionDto;
+use auth_service::dto::TokenDto;
 use auth_service::dto::UpdateDto;
+use auth_service::dto::UsernameDto;
+use auth_service::entity::AccountEntity;
+use auth_service::entity::AccountStatus;
+use auth_service::entity::AuditAction;
+use auth_service::entity::AuditOutcome;
+use auth_service::entity::ExportStatus;
+use auth_service::entity::OrganizationRole;
+use auth_service::entity::RealmEntity;
+use auth_service::entity::SessionEntity;
+use auth_service::extractor::AuthorizationExtractor;
+use auth_service::extractor::ClientExtractor;
+use auth_service::extractor::RealmExtractor;
+use auth_service::middleware::CsrfMiddleware;
+use auth_service::middleware::RealmMiddleware;
+use auth_service::middleware::cors_middleware;
+use auth_service::middleware::security_headers_middleware;
 use auth_service::service::AccountService;
+use auth_service::service::MailService;
+use auth_service::transaction::AccountTransaction;
 
 use bcrypt::verify;
 
+use chrono::Utc;
+
+use dotenv::dotenv;
+
 use env_logger::Env;
 
+use uuid::Uuid;
+
 use validator::Validate;
 
+use zip::ZipWriter;
+use zip::write::FileOptions;
+
+async fn require_elevation(transaction: &mut AccountTransaction<'_>, session: &SessionEntity, entity: &AccountEntity, password: Option<&String>) -> Result<(), Error> {
+    if session.impersonator_id.is_some() {
+        return Err(Error::Forbidden("This action is not available while impersonating"));
+    }
+
+    if session.elevated {
+        return Ok(());
+    }
+
+    match password {
+        Some(password) if transaction.verify_password(entity, password).await? => Ok(()),
+        _ => Err(Error::ReauthRequired)
+    }
+}
+
+async fn renew_expired_password(transaction: &mut AccountTransaction<'_>, realm: &RealmExtractor, client: &ClientExtractor, entity: &AccountEntity, new_password: &Option<String>) -> Result<(), Error> {
+    if !realm.password_expired(entity) {
+        return Ok(());
+    }
+
+    let password = match new_password {
+        Some(password) => password,
+        None => {
+            return Err(Error::PasswordExpired);
+        }
+    };
+
+    if !realm.accepts_password(password) {
+        return Err(Error::InvalidPassword(format!("The min size of the password is {}", realm.entity.password_min_length)));
+    }
+
+    if transaction.password_reused(entity, password, realm.entity.password_history_depth).await? {
+        return Err(Error::InvalidPassword("The password has been used before".to_string()));
+    }
+
+    transaction.update(entity.id, &None, new_password).await?;
+    transaction.audit(Some(entity.id), AuditAction::Update, AuditOutcome::Success, client).await?;
+
+    Ok(())
+}
+
+async fn audit_rejected_sign_in(service: &AccountService, realm: &RealmEntity, client: &ClientExtractor, account_id: i32, action: AuditAction) -> Result<(), Error> {
+    let mut transaction = service.transaction(realm).await?;
+
+    transaction.audit(Some(account_id), action, AuditOutcome::Failure, client).await?;
+    transaction.commit().await
+}
+
+async fn sign_in(transaction: &mut AccountTransaction<'_>, service: &AccountService, mail: &MailService, client: &ClientExtractor, entity: &AccountEntity, action: AuditAction) -> Result<SessionEntity, Error> {
+    let suspended = match entity.suspended_until {
+        Some(suspended_until) => suspended_until > Utc::now(),
+        None => true
+    };
+
+    match entity.status {
+        AccountStatus::Unverified if matches!(action, AuditAction::LoginLink | AuditAction::LoginCode) => {
+            transaction.activate(entity.id).await?;
+            transaction.audit(Some(entity.id), AuditAction::Verify, AuditOutcome::Success, client).await?;
+        },
+        AccountStatus::Unverified => {
+            return Err(Error::Forbidden("The email address has not been verified yet"));
+        },
+        AccountStatus::Suspended if suspended => {
+            let mut message = "The account has been suspended".to_string();
+
+            if let Some(suspended_reason) = &entity.suspended_reason {
+                message = format!("{}: {}", message, suspended_reason);
+            }
+
+            if let Some(suspended_until) = &entity.suspended_until {
+                message = format!("{} (until {})", message, suspended_until.to_rfc3339());
+            }
+
+            return Err(Error::AccountSuspended(message));
+        },
+        AccountStatus::Suspended => {
+            transaction.activate(entity.id).await?;
+        },
+        AccountStatus::PendingDeletion => {
+            transaction.activate(entity.id).await?;
+            transaction.audit(Some(entity.id), AuditAction::Restore, AuditOutcome::Success, client).await?;
+        },
+        AccountStatus::Active => ()
+    }
+
+    let session = transaction.create_session(entity.id, service.sudo_mode_window()).await?;
+
+    if transaction.remember_device(entity.id, client).await? && service.new_device_notifications() {
+        let sent = mail.send(
+            &entity.email,
+            "New sign-in to your account",
+            format!(
+                "Your account has been signed in from a new device.\n\nIP address: {}\nUser agent: {}\n\nIf this wasn't you, sign out this device by opening the following link and change your password:\n{}",
+                client.ip_address.as_deref().unwrap_or("unknown"),
+                client.user_agent.as_deref().unwrap_or("unknown"),
+                mail.link("/session/revoke", &session.revoke_token)
+            )
+        ).await;
+
+        if let Err(error) = sent {
+            log::error!("Sending the new sign-in notification has failed: {}", error);
+        }
+    }
+
+    transaction.audit(Some(entity.id), action, AuditOutcome::Success, client).await?;
+
+    Ok(session)
+}
+
+async fn purge(service: &AccountService) -> Result<usize, Error> {
+    let client = ClientExtractor { ip_address: None, user_agent: None };
+    let mut total = 0;
+
+    for realm in service.find_realms().await? {
+        let mut transaction = service.transaction(&realm).await?;
+
+        transaction.delete_expired_exports().await?;
+        transaction.delete_expired_username_reservations().await?;
+        transaction.delete_expired_sessions().await?;
+        transaction.delete_expired_login_codes().await?;
+        transaction.delete_old_password_history(realm.password_history_depth).await?;
+        transaction.commit().await?;
+
+        loop {
+            let mut transaction = service.transaction(&realm).await?;
+            let ids = transaction.purge(service.deletion_grace_period(), 100).await?;
+
+            for id in &ids {
+                transaction.audit(Some(*id), AuditAction::Purge, AuditOutcome::Success, &client).await?;
+            }
+
+            transaction.commit().await?;
+
+            if ids.is_empty() {
+                break;
+            }
+
+            total += ids.len();
+        }
+    }
+
+    Ok(total)
+}
+
+async fn create_export_archive(transaction: &mut AccountTransaction<'_>, entity: AccountEntity) -> Result<Vec<u8>, Error> {
+    let roles = transaction.find_roles(entity.id).await?;
+    let joined_organizations = transaction.find_organizations(entity.id).await?;
+    let sessions = transaction.find_sessions(entity.id).await?;
+    let devices = transaction.find_devices(entity.id).await?;
+    let events = transaction.find_audit_events(entity.id, i64::MAX).await?;
+
+    let profile = ProfileDto::from(&entity);
+
+    let mut account = AdminAccountDto::from(entity);
+    account.account.roles = roles;
+    account.sessions = sessions.into_iter().map(SessionDto::from).collect();
+
+    let files = [
+        ("account.json", serde_json::to_vec_pretty(&account)?),
+        ("profile.json", serde_json::to_vec_pretty(&profile)?),
+        ("organizations.json", serde_json::to_vec_pretty(&joined_organizations.into_iter().map(OrganizationDto::from).collect::<Vec<_>>())?),
+        ("devices.json", serde_json::to_vec_pretty(&devices.into_iter().map(DeviceDto::from).collect::<Vec<_>>())?),
+        ("audit_events.json", serde_json::to_vec_pretty(&events.into_iter().map(AuditEventDto::from).collect::<Vec<_>>())?)
+    ];
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, content) in files {
+        writer.start_file(name, FileOptions::default())?;
+        writer.write_all(&content)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+async fn complete_export(service: &AccountService, mail: &MailService, realm: &RealmEntity, account_id: i32, token: &String) -> Result<(), Error> {
+    let mut transaction = service.transaction(realm).await?;
+
+    let entity = match transaction.find_by_id(account_id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(());
+        }
+    };
+
+    let email = entity.email.clone();
+    let archive = create_export_archive(&mut transaction, entity).await;
+    let ready = archive.is_ok();
+
+    drop(transaction);
+
+    let mut transaction = service.transaction(realm).await?;
+
+    transaction.complete_export(token, archive.ok()).await?;
+    transaction.commit().await?;
+
+    match ready {
+        true => mail.send(
+            &email,
+            "Your data export is ready",
+            format!("The export of your account data is ready. Download it within the next 7 days by opening the following link:\n{}", mail.link("/export", token))
+        ).await,
+        false => Err(Error::ExportError)
+    }
+}
+
+async fn require_organization_role(transaction: &mut AccountTransaction<'_>, organization_id: i32, account_id: i32, role: OrganizationRole) -> Result<OrganizationRole, Error> {
+    match transaction.find_membership_role(organization_id, account_id).await? {
+        Some(granted) if granted >= role => Ok(granted),
+        Some(_) => Err(Error::Forbidden("Insufficient organization role")),
+        None => Err(Error::Forbidden("Not a member of this organization"))
+    }
+}
+
 #[get("/api/user")]
-async fn get(service: Data<AccountService>, request: HttpRequest) -> Result<impl Responder, Error> {
+async fn get(service: Data<AccountService>, realm: RealmExtractor, request: HttpRequest) -> Result<impl Responder, Error> {
     let read_key = match request.cookie("READ_KEY") {
         Some(read_key_cookie) => read_key_cookie.value().to_string(),
         None => {
@@ -44,7 +309,7 @@ async fn get(service: Data<AccountService>, request: HttpRequest) -> Result<impl
         }
     };
 
-    let mut transaction = service.transaction().await?;
+    let mut transaction = service.transaction(&realm.entity).await?;
 
     let entity = match transaction.find_by_read_key(&read_key).await? {
         Some(entity) => entity,
@@ -53,13 +318,21 @@ async fn get(service: Data<AccountService>, request: HttpRequest) -> Result<impl
         }
     };
 
+    let roles = transaction.find_roles(entity.id).await?;
+    let organization_id = transaction.find_session_by_read_key(&read_key).await?.and_then(|session| session.organization_id);
+
+    let impersonator_id = match entity.impersonator_id {
+        Some(impersonator_id) => transaction.find_by_id(impersonator_id).await?.map(|impersonator| impersonator.public_id),
+        None => None
+    };
+
     transaction.commit().await?;
 
-    Ok(HttpResponse::Ok().json(AccountDto::from(entity)))
+    Ok(HttpResponse::Ok().json(AccountDto { roles, organization_id, impersonator_id, ..AccountDto::from(entity) }))
 }
 
 #[get("/api/user/authenticate")]
-async fn authenticate(service: Data<AccountService>, request: HttpRequest) -> Result<impl Responder, Error> {
+async fn authenticate(service: Data<AccountService>, realm: RealmExtractor, request: HttpRequest) -> Result<impl Responder, Error> {
     let read_key = match request.cookie("READ_KEY") {
         Some(read_key_cookie) => read_key_cookie.value().to_string(),
         None => {
@@ -67,19 +340,55 @@ async fn authenticate(service: Data<AccountService>, request: HttpRequest) -> Re
         }
     };
 
-    let mut transaction = service.transaction().await?;
+    let mut transaction = service.transaction(&realm.entity).await?;
 
-    if transaction.find_by_read_key(&read_key).await?.is_none() {
-        return Ok(HttpResponse::Forbidden().body("Invalid read key provided"));
-    }
+    let entity = match transaction.find_by_read_key(&read_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid read key provided"));
+        }
+    };
+
+    let roles = transaction.find_roles(entity.id).await?;
 
+    transaction.record_activity(&entity, service.last_seen_interval()).await?;
     transaction.commit().await?;
 
-    Ok(HttpResponse::Ok().body("Ok"))
+    Ok(
+        HttpResponse::Ok()
+        .insert_header(("X-Account-Id", entity.public_id.to_string()))
+        .insert_header(("X-Account-Roles", roles.join(",")))
+        .body("Ok")
+    )
+}
+
+#[get("/api/user/audit")]
+async fn audit(service: Data<AccountService>, realm: RealmExtractor, request: HttpRequest) -> Result<impl Responder, Error> {
+    let read_key = match request.cookie("READ_KEY") {
+        Some(read_key_cookie) => read_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No read key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_read_key(&read_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid read key provided"));
+        }
+    };
+
+    let events = transaction.find_audit_events(entity.id, 50).await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(events.into_iter().map(AuditEventDto::from).collect::<Vec<_>>()))
 }
 
 #[post("/api/user/logout")]
-async fn logout(service: Data<AccountService>, request: HttpRequest) -> Result<impl Responder, Error> {
+async fn logout(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, request: HttpRequest) -> Result<impl Responder, Error> {
     let write_key = match request.cookie("WRITE_KEY") {
         Some(write_key_cookie) => write_key_cookie.value().to_string(),
         None => {
@@ -87,14 +396,20 @@ async fn logout(service: Data<AccountService>, request: HttpRequest) -> Result<i
         }
     };
 
-    let mut transaction = service.transaction().await?;
+    let mut transaction = service.transaction(&realm.entity).await?;
 
-    if transaction.find_by_write_key(&write_key).await?.is_none() {
-        return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
-    }
+    let session = match transaction.find_session_by_write_key(&write_key).await? {
+        Some(session) => session,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    transaction.delete_session(session.id).await?;
+    transaction.audit(Some(session.account_id), AuditAction::Logout, AuditOutcome::Success, &client).await?;
 
-    let mut write_key_cookie = Cookie::build("WRITE_KEY", "").finish();
-    let mut read_key_cookie = Cookie::build("READ_KEY", "").finish();
+    let mut write_key_cookie = realm.cookie("WRITE_KEY", "", "/api/user").finish();
+    let mut read_key_cookie = realm.cookie("READ_KEY", "", "/api/user").finish();
 
     write_key_cookie.make_removal();
     read_key_cookie.make_removal();
@@ -110,24 +425,53 @@ async fn logout(service: Data<AccountService>, request: HttpRequest) -> Result<i
 }
 
 #[post("/api/user/login")]
-async fn login(service: Data<AccountService>, dto: Json<FormDto>) -> Result<impl Responder, Error> {
+async fn login(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, dto: Json<FormDto>) -> Result<impl Responder, Error> {
     dto.validate()?;
     
-    let mut transaction = service.transaction().await?;
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match dto.email.contains('@') {
+        true => transaction.find_by_email(&dto.email).await?,
+        false => transaction.find_by_username(&dto.email).await?
+    };
     
-    let entity = match transaction.find_by_email(&dto.email).await? {
+    let entity = match entity {
         Some(entity) => entity,
         None => {
+            transaction.audit(None, AuditAction::Login, AuditOutcome::Failure, &client).await?;
+            transaction.commit().await?;
+
             return Ok(HttpResponse::Forbidden().body("Invalid login data"));
         }
     };
 
-    if !verify(&dto.password, &entity.password_hash)? {
+    if !transaction.verify_password(&entity, &dto.password).await? {
+        transaction.audit(Some(entity.id), AuditAction::Login, AuditOutcome::Failure, &client).await?;
+        transaction.commit().await?;
+
         return Ok(HttpResponse::Forbidden().body("Invalid login data"));
     }
 
-    let write_key_cookie = Cookie::build("WRITE_KEY", &entity.write_key).finish();
-    let read_key_cookie = Cookie::build("READ_KEY", &entity.read_key).finish();
+    if let Err(error) = renew_expired_password(&mut transaction, &realm, &client, &entity, &dto.new_password).await {
+        if let Error::PasswordExpired = error {
+            transaction.commit().await?;
+        }
+
+        return Err(error);
+    }
+
+    let session = match sign_in(&mut transaction, &service, &mail, &client, &entity, AuditAction::Login).await {
+        Err(error @ (Error::Forbidden(_) | Error::AccountSuspended(_))) => {
+            drop(transaction);
+            audit_rejected_sign_in(&service, &realm.entity, &client, entity.id, AuditAction::Login).await?;
+
+            return Err(error);
+        },
+        session => session?
+    };
+
+    let write_key_cookie = realm.cookie("WRITE_KEY", &session.write_key, "/api/user").finish();
+    let read_key_cookie = realm.cookie("READ_KEY", &session.read_key, "/api/user").finish();
 
     transaction.commit().await?;
 
@@ -139,23 +483,63 @@ async fn login(service: Data<AccountService>, dto: Json<FormDto>) -> Result<impl
     )
 }
 
-#[post("/api/user/register")]
-async fn register(service: Data<AccountService>, dto: Json<FormDto>) -> Result<impl Responder, Error> {
+#[post("/api/user/login/link")]
+async fn request_login_link(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, dto: Json<EmailDto>) -> Result<impl Responder, Error> {
     dto.validate()?;
 
-    let mut transaction = service.transaction().await?;
+    let mut transaction = service.transaction(&realm.entity).await?;
 
-    transaction.lock().await?;
+    let entity = match transaction.find_by_email(&dto.email).await? {
+        Some(entity) if transaction.login_link_allowed(entity.id).await? => Some(entity),
+        _ => None
+    };
 
-    let entity = match transaction.create(&dto.email, &dto.password).await {
-        Ok(entity) => entity,
-        Err(_) => {
-            return Ok(HttpResponse::Conflict().body("Account already exists"));
+    if let Some(entity) = entity {
+        let token = transaction.create_login_link(entity.id).await?;
+
+        let sent = mail.send(
+            &entity.email,
+            "Your sign-in link",
+            format!("Sign in to your account by opening the following link. The link can be used once and expires in 15 minutes:\n{}", mail.link("/login/link", &token))
+        ).await;
+
+        if let Err(error) = sent {
+            log::error!("Sending the sign-in link has failed: {}", error);
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/login/link/consume")]
+async fn consume_login_link(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.consume_login_link(&dto.token).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
         }
     };
-    
-    let write_key_cookie = Cookie::build("WRITE_KEY", &entity.write_key).finish();
-    let read_key_cookie = Cookie::build("READ_KEY", &entity.read_key).finish();
+
+    renew_expired_password(&mut transaction, &realm, &client, &entity, &dto.new_password).await?;
+
+    let session = match sign_in(&mut transaction, &service, &mail, &client, &entity, AuditAction::LoginLink).await {
+        Err(error @ (Error::Forbidden(_) | Error::AccountSuspended(_))) => {
+            drop(transaction);
+            audit_rejected_sign_in(&service, &realm.entity, &client, entity.id, AuditAction::LoginLink).await?;
+
+            return Err(error);
+        },
+        session => session?
+    };
+
+    let write_key_cookie = realm.cookie("WRITE_KEY", &session.write_key, "/api/user").finish();
+    let read_key_cookie = realm.cookie("READ_KEY", &session.read_key, "/api/user").finish();
 
     transaction.commit().await?;
 
@@ -167,70 +551,1155 @@ async fn register(service: Data<AccountService>, dto: Json<FormDto>) -> Result<i
     )
 }
 
-#[put("/api/user/update")]
-async fn update(service: Data<AccountService>, request: HttpRequest, dto: Json<UpdateDto>) -> Result<impl Responder, Error> {
+#[post("/api/user/login/code")]
+async fn request_login_code(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, dto: Json<EmailDto>) -> Result<impl Responder, Error> {
     dto.validate()?;
 
-    let write_key = match request.cookie("WRITE_KEY") {
-        Some(write_key_cookie) => write_key_cookie.value().to_string(),
-        None => {
-            return Ok(HttpResponse::Forbidden().body("No write key provided"));
-        }
+    let challenge = Uuid::new_v4().to_string();
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_email(&dto.email).await? {
+        Some(entity) if transaction.login_code_allowed(entity.id).await? => Some(entity),
+        _ => None
     };
 
-    let mut transaction = service.transaction().await?;
+    if let Some(entity) = entity {
+        let code = transaction.create_login_code(entity.id, &challenge).await?;
 
-    if transaction.find_by_write_key(&write_key).await?.is_none() {
-        return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
-    }
+        let sent = mail.send(
+            &entity.email,
+            "Your sign-in code",
+            format!("Your sign-in code is {}. The code expires in 10 minutes.", code)
+        ).await;
 
-    if transaction.update(&write_key, &dto.email, &dto.password).await.is_err() {
-        return Ok(HttpResponse::Conflict().body("Email is already registered"));
+        if let Err(error) = sent {
+            log::error!("Sending the sign-in code has failed: {}", error);
+        }
     }
 
+    let challenge_cookie = realm.cookie("LOGIN_CODE_CHALLENGE", &challenge, "/api/user/login/code").http_only(true).finish();
+
     transaction.commit().await?;
 
-    Ok(HttpResponse::Ok().body("Ok"))
+    Ok(
+        HttpResponse::Ok()
+        .cookie(challenge_cookie)
+        .body("Ok")
+    )
 }
 
-#[delete("/api/user/delete")]
-async fn delete(service: Data<AccountService>, request: HttpRequest) -> Result<impl Responder, Error> {
-    let write_key = match request.cookie("WRITE_KEY") {
-        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+#[post("/api/user/login/code/verify")]
+async fn verify_login_code(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, request: HttpRequest, dto: Json<CodeDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let challenge = match request.cookie("LOGIN_CODE_CHALLENGE") {
+        Some(challenge_cookie) => challenge_cookie.value().to_string(),
         None => {
-            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+            return Ok(HttpResponse::Forbidden().body("No login code challenge provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let login_code = match transaction.find_login_code(&challenge).await? {
+        Some(login_code) => login_code,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid code"));
         }
     };
 
-    let mut transaction = service.transaction().await?;
+    if !verify(&dto.code, &login_code.code_hash)? {
+        transaction.fail_login_code(login_code.id).await?;
+        transaction.audit(Some(login_code.account_id), AuditAction::LoginCode, AuditOutcome::Failure, &client).await?;
+        transaction.commit().await?;
 
-    if transaction.find_by_write_key(&write_key).await?.is_none() {
-        return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        return Ok(HttpResponse::Forbidden().body("Invalid code"));
     }
 
-    transaction.delete(&write_key).await?;
+    let entity = match transaction.use_login_code(login_code.id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid code"));
+        }
+    };
+
+    renew_expired_password(&mut transaction, &realm, &client, &entity, &dto.new_password).await?;
+
+    let session = match sign_in(&mut transaction, &service, &mail, &client, &entity, AuditAction::LoginCode).await {
+        Err(error @ (Error::Forbidden(_) | Error::AccountSuspended(_))) => {
+            drop(transaction);
+            audit_rejected_sign_in(&service, &realm.entity, &client, entity.id, AuditAction::LoginCode).await?;
+
+            return Err(error);
+        },
+        session => session?
+    };
+
+    let mut challenge_cookie = realm.cookie("LOGIN_CODE_CHALLENGE", "", "/api/user/login/code").finish();
+    let write_key_cookie = realm.cookie("WRITE_KEY", &session.write_key, "/api/user").finish();
+    let read_key_cookie = realm.cookie("READ_KEY", &session.read_key, "/api/user").finish();
+
+    challenge_cookie.make_removal();
+
     transaction.commit().await?;
 
-    Ok(HttpResponse::Ok().body("Ok"))
+    Ok(
+        HttpResponse::Ok()
+        .cookie(challenge_cookie)
+        .cookie(write_key_cookie)
+        .cookie(read_key_cookie)
+        .body("Ok")
+    )
 }
 
-#[actix_web::main]
-async fn main() -> io::Result<()> {
-    env_logger::init_from_env(Env::default().default_filter_or("info"));
+#[post("/api/user/register")]
+async fn register(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, dto: Json<FormDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    if !realm.entity.registration_open {
+        return Ok(HttpResponse::Forbidden().body("Registration is closed"));
+    }
+
+    if !validator::validate_email(&dto.email) {
+        return Ok(HttpResponse::BadRequest().body("Invalid email address"));
+    }
+
+    if !realm.accepts_password(&dto.password) {
+        return Ok(HttpResponse::BadRequest().body(format!("The min size of the password is {}", realm.entity.password_min_length)));
+    }
+
+    if let Some(username) = &dto.username {
+        if let Some(error) = realm.username_error(username)? {
+            return Ok(HttpResponse::BadRequest().body(error));
+        }
+    }
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    transaction.lock().await?;
+
+    if transaction.find_by_email(&dto.email).await?.is_some() {
+        return Ok(HttpResponse::Conflict().body("Account already exists"));
+    }
+
+    if let Some(username) = &dto.username {
+        if !transaction.username_available(username, None).await? {
+            return Ok(HttpResponse::Conflict().body("Username is already taken"));
+        }
+    }
+
+    let status = match realm.entity.email_verification {
+        true => AccountStatus::Unverified,
+        false => AccountStatus::Active
+    };
+
+    let entity = match transaction.create(&dto.email, &dto.password, status).await {
+        Ok(entity) => entity,
+        Err(_) => {
+            return Ok(HttpResponse::Conflict().body("Account already exists"));
+        }
+    };
+
+    if let Some(username) = &dto.username {
+        transaction.update_username(&entity, username, 0).await?;
+    }
+
+    if status == AccountStatus::Unverified {
+        let token = transaction.create_login_link(entity.id).await?;
+
+        mail.send(
+            &entity.email,
+            "Verify your email address",
+            format!("Verify your email address and sign in by opening the following link. The link can be used once and expires in 15 minutes:\n{}", mail.link("/login/link", &token))
+        ).await?;
+
+        transaction.audit(Some(entity.id), AuditAction::Register, AuditOutcome::Success, &client).await?;
+        transaction.commit().await?;
+
+        return Ok(HttpResponse::Ok().body("Ok"));
+    }
+
+    let session = transaction.create_session(entity.id, service.sudo_mode_window()).await?;
+
+    transaction.remember_device(entity.id, &client).await?;
+    transaction.audit(Some(entity.id), AuditAction::Register, AuditOutcome::Success, &client).await?;
     
-    HttpServer::new(|| {
+    let write_key_cookie = realm.cookie("WRITE_KEY", &session.write_key, "/api/user").finish();
+    let read_key_cookie = realm.cookie("READ_KEY", &session.read_key, "/api/user").finish();
+
+    transaction.commit().await?;
+
+    Ok(
+        HttpResponse::Ok()
+        .cookie(write_key_cookie)
+        .cookie(read_key_cookie)
+        .body("Ok")
+    )
+}
+
+#[post("/api/user/session/revoke")]
+async fn revoke_session(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let account_id = match transaction.revoke_session(&dto.token).await? {
+        Some(account_id) => account_id,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
+        }
+    };
+
+    transaction.audit(Some(account_id), AuditAction::RevokeSession, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/reauthenticate")]
+async fn reauthenticate(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, request: HttpRequest, dto: Json<PasswordDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let session = match transaction.find_session_by_write_key(&write_key).await? {
+        Some(session) => session,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    let entity = match transaction.find_by_write_key(&write_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    if session.impersonator_id.is_some() {
+        return Ok(HttpResponse::Forbidden().body("This action is not available while impersonating"));
+    }
+
+    if !transaction.verify_password(&entity, &dto.password).await? {
+        transaction.audit(Some(entity.id), AuditAction::Reauthenticate, AuditOutcome::Failure, &client).await?;
+        transaction.commit().await?;
+
+        return Ok(HttpResponse::Forbidden().body("Invalid password"));
+    }
+
+    transaction.elevate_session(session.id, service.sudo_mode_window()).await?;
+    transaction.audit(Some(entity.id), AuditAction::Reauthenticate, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[put("/api/user/update")]
+async fn update(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, request: HttpRequest, dto: Json<UpdateDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    if dto.password.as_ref().is_some_and(|password| !realm.accepts_password(password)) {
+        return Ok(HttpResponse::BadRequest().body(format!("The min size of the password is {}", realm.entity.password_min_length)));
+    }
+
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let session = match transaction.find_session_by_write_key(&write_key).await? {
+        Some(session) => session,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    let entity = match transaction.find_by_write_key(&write_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    require_elevation(&mut transaction, &session, &entity, dto.current_password.as_ref()).await?;
+
+    if let Some(password) = &dto.password {
+        if transaction.password_reused(&entity, password, realm.entity.password_history_depth).await? {
+            return Ok(HttpResponse::BadRequest().body("The password has been used before"));
+        }
+    }
+
+    transaction.update(entity.id, &None, &dto.password).await?;
+
+    if let Some(email) = dto.email.as_ref().filter(|email| **email != entity.email) {
+        if transaction.find_by_email(email).await?.is_some_and(|existing| existing.id != entity.id) {
+            return Ok(HttpResponse::Conflict().body("Email is already registered"));
+        }
+
+        let change = transaction.create_email_change(&entity, email).await?;
+
+        mail.send(
+            &change.new_email,
+            "Confirm your new email address",
+            format!("Confirm your new email address by opening the following link:\n{}", mail.link("/email/confirm", &change.confirm_token))
+        ).await?;
+    }
+
+    transaction.audit(Some(entity.id), AuditAction::Update, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[put("/api/user/username")]
+async fn update_username(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, request: HttpRequest, dto: Json<UsernameDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    if let Some(error) = realm.username_error(&dto.username)? {
+        return Ok(HttpResponse::BadRequest().body(error));
+    }
+
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_write_key(&write_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    if entity.impersonator_id.is_some() {
+        return Ok(HttpResponse::Forbidden().body("This action is not available while impersonating"));
+    }
+
+    if let Some(username_changed_at) = entity.username_changed_at {
+        if Utc::now().signed_duration_since(username_changed_at).num_seconds() < service.username_change_cooldown() as i64 {
+            return Ok(HttpResponse::TooManyRequests().body("The username was changed too recently"));
+        }
+    }
+
+    if !transaction.username_available(&dto.username, Some(entity.id)).await? {
+        return Ok(HttpResponse::Conflict().body("Username is already taken"));
+    }
+
+    transaction.update_username(&entity, &dto.username, service.username_reservation_period()).await?;
+    transaction.audit(Some(entity.id), AuditAction::UpdateUsername, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[put("/api/user/profile")]
+async fn update_profile(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, request: HttpRequest, dto: Json<ProfileDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let errors = realm.attribute_errors(&dto.attributes)?;
+
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().body(errors.join("\n")));
+    }
+
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_write_key(&write_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    if entity.impersonator_id.is_some() {
+        return Ok(HttpResponse::Forbidden().body("This action is not available while impersonating"));
+    }
+
+    transaction.update_profile(entity.id, &dto.display_name, &dto.avatar_url, &dto.locale, &dto.timezone, &dto.attributes).await?;
+    transaction.audit(Some(entity.id), AuditAction::UpdateProfile, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[get("/api/user/export")]
+async fn exports(service: Data<AccountService>, realm: RealmExtractor, authorization: AuthorizationExtractor) -> Result<impl Responder, Error> {
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let exports = transaction.find_exports(authorization.entity.id).await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(exports.into_iter().map(ExportDto::from).collect::<Vec<_>>()))
+}
+
+#[post("/api/user/export")]
+async fn request_export(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, authorization: AuthorizationExtractor) -> Result<impl Responder, Error> {
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    if transaction.find_exports(authorization.entity.id).await?.iter().any(|export| export.status == ExportStatus::Pending) {
+        return Ok(HttpResponse::Conflict().body("An export is already in progress"));
+    }
+
+    let token = transaction.create_export(authorization.entity.id).await?;
+
+    transaction.audit(Some(authorization.entity.id), AuditAction::Export, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    let account_id = authorization.entity.id;
+    let service = service.clone();
+    let mail = mail.clone();
+
+    actix_web::rt::spawn(async move {
+        if let Err(error) = complete_export(&service, &mail, &realm.entity, account_id, &token).await {
+            log::error!("Creating the data export of account {} has failed: {}", account_id, error);
+        }
+    });
+
+    Ok(HttpResponse::Accepted().body("Ok"))
+}
+
+#[get("/api/user/export/download")]
+async fn download_export(service: Data<AccountService>, realm: RealmExtractor, authorization: AuthorizationExtractor, dto: Query<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    if authorization.entity.impersonator_id.is_some() {
+        return Ok(HttpResponse::Forbidden().body("This action is not available while impersonating"));
+    }
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let archive = match transaction.find_export_archive(authorization.entity.id, &dto.token).await? {
+        Some(archive) => archive,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Export not found"));
+        }
+    };
+
+    transaction.commit().await?;
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(("Content-Disposition", "attachment; filename=\"export.zip\""))
+        .body(archive)
+    )
+}
+
+#[post("/api/user/email/confirm")]
+async fn confirm_email(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let change = match transaction.confirm_email_change(&dto.token).await? {
+        Some(change) => change,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
+        }
+    };
+
+    if transaction.update(change.account_id, &Some(change.new_email.clone()), &None).await.is_err() {
+        return Ok(HttpResponse::Conflict().body("Email is already registered"));
+    }
+
+    mail.send(
+        &change.old_email,
+        "Your email address has been changed",
+        format!("The email address of your account has been changed to {}. If this wasn't you, undo the change by opening the following link:\n{}", change.new_email, mail.link("/email/undo", &change.undo_token))
+    ).await?;
+
+    transaction.audit(Some(change.account_id), AuditAction::ConfirmEmail, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/email/undo")]
+async fn undo_email(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let change = match transaction.undo_email_change(&dto.token).await? {
+        Some(change) => change,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
+        }
+    };
+
+    if transaction.update(change.account_id, &Some(change.old_email.clone()), &None).await.is_err() {
+        return Ok(HttpResponse::Conflict().body("Email is already registered"));
+    }
+
+    transaction.delete_sessions(change.account_id).await?;
+    transaction.audit(Some(change.account_id), AuditAction::UndoEmail, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[delete("/api/user/delete")]
+async fn delete(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, request: HttpRequest, dto: Option<Json<PasswordDto>>) -> Result<impl Responder, Error> {
+    if let Some(dto) = &dto {
+        dto.validate()?;
+    }
+
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let session = match transaction.find_session_by_write_key(&write_key).await? {
+        Some(session) => session,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    let entity = match transaction.find_by_write_key(&write_key).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    require_elevation(&mut transaction, &session, &entity, dto.as_ref().map(|dto| &dto.password)).await?;
+
+    transaction.request_deletion(entity.id).await?;
+    transaction.delete_sessions(entity.id).await?;
+
+    mail.send(
+        &entity.email,
+        "Your account will be deleted",
+        format!("Your account will be deleted in {} days. If you change your mind, simply sign in again before then to restore it.", service.deletion_grace_period() / 86400)
+    ).await?;
+
+    transaction.audit(Some(entity.id), AuditAction::Delete, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[get("/api/user/admin/accounts")]
+async fn admin_accounts(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, query: Query<AccountQueryDto>) -> Result<impl Responder, Error> {
+    query.validate()?;
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entities = transaction.search_accounts(&query.email, &query.username, query.status, query.after, query.limit).await?;
+
+    transaction.audit_as(authorization.entity.id, None, AuditAction::ListAccounts, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(entities.into_iter().map(AdminAccountDto::from).collect::<Vec<_>>()))
+}
+
+#[get("/api/user/admin/accounts/{id}")]
+async fn admin_account(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    let roles = transaction.find_roles(entity.id).await?;
+    let sessions = transaction.find_sessions(entity.id).await?;
+
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::ViewAccount, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    let mut dto = AdminAccountDto::from(entity);
+    dto.account.roles = roles;
+    dto.sessions = sessions.into_iter().map(SessionDto::from).collect();
+
+    Ok(HttpResponse::Ok().json(dto))
+}
+
+#[post("/api/user/admin/accounts/{id}/password/reset")]
+async fn reset_password(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    transaction.reset_password(entity.id).await?;
+
+    let token = transaction.create_login_link(entity.id).await?;
+
+    mail.send(
+        &entity.email,
+        "Your password has been reset",
+        format!("Your password has been reset by an administrator and all devices have been signed out. Sign in by opening the following link and choose a new password. The link can be used once and expires in 15 minutes:\n{}", mail.link("/login/link", &token))
+    ).await?;
+
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::ResetPassword, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/admin/accounts/{id}/revoke")]
+async fn revoke_credentials(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    transaction.revoke_credentials(entity.id).await?;
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::RevokeCredentials, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[delete("/api/user/admin/accounts/{id}")]
+async fn admin_delete(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    transaction.delete(entity.id).await?;
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::AdminDelete, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/admin/accounts/{id}/impersonate")]
+async fn impersonate(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.impersonate")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    if entity.id == authorization.entity.id {
+        return Ok(HttpResponse::BadRequest().body("You can't impersonate yourself"));
+    }
+
+    if entity.status != AccountStatus::Active {
+        return Ok(HttpResponse::Conflict().body("Only active accounts can be impersonated"));
+    }
+
+    if transaction.find_permissions(entity.id).await?.iter().any(|permission| permission == "accounts.impersonate") {
+        transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::Impersonate, AuditOutcome::Failure, &client).await?;
+        transaction.commit().await?;
+
+        return Ok(HttpResponse::Forbidden().body("Accounts that can impersonate can't be impersonated"));
+    }
+
+    let session = transaction.create_impersonation_session(entity.id, authorization.entity.id, service.impersonation_duration()).await?;
+    let max_age = actix_web::cookie::time::Duration::seconds(service.impersonation_duration() as i64);
+
+    let write_key_cookie = realm.cookie("WRITE_KEY", &session.write_key, "/api/user").max_age(max_age).finish();
+    let read_key_cookie = realm.cookie("READ_KEY", &session.read_key, "/api/user").max_age(max_age).finish();
+
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::Impersonate, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(
+        HttpResponse::Ok()
+        .cookie(write_key_cookie)
+        .cookie(read_key_cookie)
+        .body("Ok")
+    )
+}
+
+#[post("/api/user/admin/import")]
+async fn import(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, query: Query<ImportQueryDto>, body: String) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let rows = match query.format {
+        ImportFormat::Csv => ImportDto::parse_csv(&body),
+        ImportFormat::JsonLines => ImportDto::parse_json_lines(&body)
+    };
+
+    let report = service.import(&realm.entity, rows, query.dry_run, Some(authorization.entity.id), &client).await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[post("/api/user/admin/accounts/{id}/roles")]
+async fn assign_role(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>, dto: Json<RoleDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+    authorization.require("roles.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    if !transaction.role_exists(&dto.role).await? {
+        return Ok(HttpResponse::NotFound().body("Role not found"));
+    }
+
+    transaction.assign_role(entity.id, &dto.role).await?;
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::AssignRole, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[delete("/api/user/admin/accounts/{id}/roles")]
+async fn revoke_role(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>, dto: Json<RoleDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+    authorization.require("roles.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    if !transaction.revoke_role(entity.id, &dto.role).await? {
+        return Ok(HttpResponse::NotFound().body("Role not assigned"));
+    }
+
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::RevokeRole, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/admin/accounts/{id}/suspend")]
+async fn suspend(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>, dto: Json<SuspensionDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) => entity,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    transaction.suspend(entity.id, &dto.reason, &dto.until).await?;
+    transaction.delete_sessions(entity.id).await?;
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::Suspend, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/admin/accounts/{id}/unsuspend")]
+async fn unsuspend(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<Uuid>) -> Result<impl Responder, Error> {
+    authorization.require("accounts.manage")?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let entity = match transaction.find_by_public_id(&id).await? {
+        Some(entity) if entity.status == AccountStatus::Suspended => entity,
+        Some(_) => {
+            return Ok(HttpResponse::Conflict().body("Account is not suspended"));
+        },
+        None => {
+            return Ok(HttpResponse::NotFound().body("Account not found"));
+        }
+    };
+
+    transaction.activate(entity.id).await?;
+    transaction.audit_as(authorization.entity.id, Some(entity.id), AuditAction::Unsuspend, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[get("/api/user/organizations")]
+async fn organizations(service: Data<AccountService>, realm: RealmExtractor, authorization: AuthorizationExtractor) -> Result<impl Responder, Error> {
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let organizations = transaction.find_organizations(authorization.entity.id).await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(organizations.into_iter().map(OrganizationDto::from).collect::<Vec<_>>()))
+}
+
+#[post("/api/user/organizations")]
+async fn create_organization(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, dto: Json<OrganizationDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let organization = transaction.create_organization(authorization.entity.id, &dto.name).await?;
+
+    transaction.audit(Some(authorization.entity.id), AuditAction::CreateOrganization, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationDto::from(organization)))
+}
+
+#[put("/api/user/organizations/{id}")]
+async fn update_organization(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<i32>, dto: Json<OrganizationDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    require_organization_role(&mut transaction, *id, authorization.entity.id, OrganizationRole::Admin).await?;
+
+    transaction.update_organization(*id, &dto.name).await?;
+    transaction.audit(Some(authorization.entity.id), AuditAction::UpdateOrganization, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[delete("/api/user/organizations/{id}")]
+async fn delete_organization(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<i32>) -> Result<impl Responder, Error> {
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    require_organization_role(&mut transaction, *id, authorization.entity.id, OrganizationRole::Owner).await?;
+
+    transaction.delete_organization(*id).await?;
+    transaction.audit(Some(authorization.entity.id), AuditAction::DeleteOrganization, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/organizations/{id}/activate")]
+async fn activate_organization(service: Data<AccountService>, realm: RealmExtractor, request: HttpRequest, id: Path<i32>) -> Result<impl Responder, Error> {
+    let write_key = match request.cookie("WRITE_KEY") {
+        Some(write_key_cookie) => write_key_cookie.value().to_string(),
+        None => {
+            return Ok(HttpResponse::Forbidden().body("No write key provided"));
+        }
+    };
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let session = match transaction.find_session_by_write_key(&write_key).await? {
+        Some(session) => session,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid write key provided"));
+        }
+    };
+
+    require_organization_role(&mut transaction, *id, session.account_id, OrganizationRole::Member).await?;
+
+    transaction.set_active_organization(session.id, Some(*id)).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[get("/api/user/organizations/{id}/members")]
+async fn members(service: Data<AccountService>, realm: RealmExtractor, authorization: AuthorizationExtractor, id: Path<i32>) -> Result<impl Responder, Error> {
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    require_organization_role(&mut transaction, *id, authorization.entity.id, OrganizationRole::Member).await?;
+
+    let memberships = transaction.find_memberships(*id).await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(memberships.into_iter().map(MembershipDto::from).collect::<Vec<_>>()))
+}
+
+#[put("/api/user/organizations/{id}/members/{account_id}")]
+async fn update_member(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, path: Path<(i32, Uuid)>, dto: Json<OrganizationRoleDto>) -> Result<impl Responder, Error> {
+    let (id, public_id) = path.into_inner();
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let granted = require_organization_role(&mut transaction, id, authorization.entity.id, OrganizationRole::Admin).await?;
+
+    let account_id = match transaction.find_by_public_id(&public_id).await? {
+        Some(entity) => entity.id,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Member not found"));
+        }
+    };
+
+    let role = match transaction.find_membership_role(id, account_id).await? {
+        Some(role) => role,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Member not found"));
+        }
+    };
+
+    if (role == OrganizationRole::Owner || dto.role == OrganizationRole::Owner) && granted != OrganizationRole::Owner {
+        return Err(Error::Forbidden("Insufficient organization role"));
+    }
+
+    if role == OrganizationRole::Owner && dto.role != OrganizationRole::Owner && transaction.count_owners(id).await? == 1 {
+        return Ok(HttpResponse::Conflict().body("An organization needs at least one owner"));
+    }
+
+    transaction.update_membership(id, account_id, dto.role).await?;
+    transaction.audit(Some(authorization.entity.id), AuditAction::UpdateMembership, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[delete("/api/user/organizations/{id}/members/{account_id}")]
+async fn remove_member(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, path: Path<(i32, Uuid)>) -> Result<impl Responder, Error> {
+    let (id, public_id) = path.into_inner();
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let account_id = match transaction.find_by_public_id(&public_id).await? {
+        Some(entity) => entity.id,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Member not found"));
+        }
+    };
+
+    let granted = match account_id == authorization.entity.id {
+        true => require_organization_role(&mut transaction, id, authorization.entity.id, OrganizationRole::Member).await?,
+        false => require_organization_role(&mut transaction, id, authorization.entity.id, OrganizationRole::Admin).await?
+    };
+
+    let role = match transaction.find_membership_role(id, account_id).await? {
+        Some(role) => role,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Member not found"));
+        }
+    };
+
+    if role == OrganizationRole::Owner && granted != OrganizationRole::Owner {
+        return Err(Error::Forbidden("Insufficient organization role"));
+    }
+
+    if role == OrganizationRole::Owner && transaction.count_owners(id).await? == 1 {
+        return Ok(HttpResponse::Conflict().body("An organization needs at least one owner"));
+    }
+
+    transaction.delete_membership(id, account_id).await?;
+    transaction.audit(Some(authorization.entity.id), AuditAction::RemoveMembership, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/organizations/{id}/invitations")]
+async fn invite(service: Data<AccountService>, realm: RealmExtractor, mail: Data<MailService>, client: ClientExtractor, authorization: AuthorizationExtractor, id: Path<i32>, dto: Json<InvitationDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let granted = require_organization_role(&mut transaction, *id, authorization.entity.id, OrganizationRole::Admin).await?;
+
+    if dto.role == OrganizationRole::Owner && granted != OrganizationRole::Owner {
+        return Err(Error::Forbidden("Insufficient organization role"));
+    }
+
+    let organization = match transaction.find_organization(*id, authorization.entity.id).await? {
+        Some(organization) => organization,
+        None => {
+            return Ok(HttpResponse::NotFound().body("Organization not found"));
+        }
+    };
+
+    let token = transaction.create_invitation(*id, &dto.email, dto.role).await?;
+
+    mail.send(
+        &dto.email,
+        "You have been invited to an organization",
+        format!(
+            "{} has invited you to join {}. To accept the invitation open the following link:\n{}\n\nTo decline it open this link instead:\n{}\n\nThe invitation expires in 7 days.",
+            authorization.entity.email,
+            organization.name,
+            mail.link("/invitation/accept", &token),
+            mail.link("/invitation/decline", &token)
+        )
+    ).await?;
+
+    transaction.audit(Some(authorization.entity.id), AuditAction::Invite, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[post("/api/user/invitations/accept")]
+async fn accept_invitation(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, authorization: AuthorizationExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    let invitation = match transaction.consume_invitation(&dto.token).await? {
+        Some(invitation) => invitation,
+        None => {
+            return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
+        }
+    };
+
+    if !transaction.same_email(&invitation.email, &authorization.entity.email) {
+        return Ok(HttpResponse::Forbidden().body("The invitation was sent to a different email address"));
+    }
+
+    transaction.create_membership(invitation.organization_id, authorization.entity.id, invitation.role).await?;
+    transaction.audit(Some(authorization.entity.id), AuditAction::AcceptInvitation, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationDto {
+        id: invitation.organization_id,
+        name: invitation.organization_name,
+        role: Some(invitation.role)
+    }))
+}
+
+#[post("/api/user/invitations/decline")]
+async fn decline_invitation(service: Data<AccountService>, realm: RealmExtractor, client: ClientExtractor, dto: Json<TokenDto>) -> Result<impl Responder, Error> {
+    dto.validate()?;
+
+    let mut transaction = service.transaction(&realm.entity).await?;
+
+    if transaction.consume_invitation(&dto.token).await?.is_none() {
+        return Ok(HttpResponse::Forbidden().body("Invalid token provided"));
+    }
+
+    transaction.audit(None, AuditAction::DeclineInvitation, AuditOutcome::Success, &client).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body("Ok"))
+}
+
+#[actix_web::main]
+async fn main() -> io::Result<()> {
+    dotenv().ok();
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+
+    let service = AccountService::new();
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(service.purge_interval()));
+
+        loop {
+            interval.tick().await;
+
+            match purge(&service).await {
+                Ok(0) => (),
+                Ok(count) => log::info!("Purged {} deleted accounts", count),
+                Err(error) => log::error!("Purging deleted accounts has failed: {}", error)
+            }
+        }
+    });
+    
+    let mail = Data::new(MailService::new());
+
+    HttpServer::new(move || {
         let service = AccountService::new();
 
         App::new()
+        .wrap(CsrfMiddleware::new())
+        .wrap(security_headers_middleware())
+        .wrap(cors_middleware())
+        .wrap(RealmMiddleware)
         .wrap(Logger::default())
         .app_data(Data::new(service))
+        .app_data(mail.clone())
         .service(get)
         .service(authenticate)
+        .service(audit)
         .service(logout)
         .service(login)
+        .service(request_login_link)
+        .service(consume_login_link)
+        .service(request_login_code)
+        .service(verify_login_code)
         .service(register)
+        .service(revoke_session)
+        .service(reauthenticate)
         .service(update)
+        .service(update_profile)
+        .service(update_username)
+        .service(exports)
+        .service(request_export)
+        .service(download_export)
+        .service(confirm_email)
+        .service(undo_email)
         .service(delete)
+        .service(admin_accounts)
+        .service(admin_account)
+        .service(reset_password)
+        .service(revoke_credentials)
+        .service(admin_delete)
+        .service(impersonate)
+        .service(import)
+        .service(assign_role)
+        .service(revoke_role)
+        .service(suspend)
+        .service(unsuspend)
+        .service(organizations)
+        .service(create_organization)
+        .service(update_organization)
+        .service(delete_organization)
+        .service(activate_organization)
+        .service(members)
+        .service(update_member)
+        .service(remove_member)
+        .service(invite)
+        .service(accept_invitation)
+        .service(decline_invitation)
     })
     .bind(("0.0.0.0", 7878))?
     .run()
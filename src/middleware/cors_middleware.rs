@@ -0,0 +1,30 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use actix_cors::Cors;
+use actix_web::http::header::CONTENT_TYPE;
+
+use dotenv_codegen::dotenv;
+
+pub fn cors_middleware() -> Cors {
+    let mut cors = Cors::default()
+    .allowed_methods(dotenv!("CORS_ALLOWED_METHODS").split(',').map(|method| method.trim()).filter(|method| !method.is_empty()))
+    .allowed_header(CONTENT_TYPE)
+    .max_age(3600);
+
+    for origin in dotenv!("CORS_ALLOWED_ORIGINS").split(',').map(|origin| origin.trim()).filter(|origin| !origin.is_empty()) {
+        cors = cors.allowed_origin(origin);
+    }
+
+    if dotenv!("CORS_ALLOW_CREDENTIALS").parse().expect("CORS_ALLOW_CREDENTIALS has to be true or false") {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
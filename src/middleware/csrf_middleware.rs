@@ -0,0 +1,123 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::future::Future;
+use std::future::Ready;
+use std::future::ready;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::HttpResponse;
+use actix_web::body::EitherBody;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::dev::forward_ready;
+use actix_web::http::Method;
+use actix_web::http::header::ORIGIN;
+use actix_web::http::header::REFERER;
+
+use dotenv_codegen::dotenv;
+
+pub struct CsrfMiddleware {
+    allowed_origins: Rc<Vec<String>>
+}
+
+impl CsrfMiddleware {
+    pub fn new() -> CsrfMiddleware {
+        let allowed_origins = dotenv!("CSRF_ALLOWED_ORIGINS")
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect();
+
+        CsrfMiddleware { allowed_origins: Rc::new(allowed_origins) }
+    }
+}
+
+impl Default for CsrfMiddleware {
+    fn default() -> CsrfMiddleware {
+        CsrfMiddleware::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = CsrfMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CsrfMiddlewareService {
+            service,
+            allowed_origins: self.allowed_origins.clone()
+        }))
+    }
+}
+
+pub struct CsrfMiddlewareService<S> {
+    service: S,
+    allowed_origins: Rc<Vec<String>>
+}
+
+impl<S> CsrfMiddlewareService<S> {
+    fn is_safe(request: &ServiceRequest) -> bool {
+        matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+    }
+
+    fn origin(request: &ServiceRequest) -> Option<String> {
+        if let Some(origin) = request.headers().get(ORIGIN) {
+            return origin.to_str().ok().map(|origin| origin.to_string());
+        }
+
+        let referer = request.headers().get(REFERER)?.to_str().ok()?;
+        let scheme_end = referer.find("://")? + 3;
+        let origin_end = referer[scheme_end..].find('/').map_or(referer.len(), |index| scheme_end + index);
+
+        Some(referer[..origin_end].to_string())
+    }
+
+    fn is_allowed(&self, request: &ServiceRequest) -> bool {
+        match Self::origin(request) {
+            Some(origin) => self.allowed_origins.contains(&origin),
+            None => request.cookie("WRITE_KEY").is_none()
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        if !Self::is_safe(&request) && !self.is_allowed(&request) {
+            let response = request.into_response(HttpResponse::Forbidden().body("Cross-site request rejected"));
+            return Box::pin(async { Ok(response.map_into_right_body()) });
+        }
+
+        let future = self.service.call(request);
+
+        Box::pin(async move {
+            Ok(future.await?.map_into_left_body())
+        })
+    }
+}
@@ -0,0 +1,21 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+mod cors_middleware;
+pub use cors_middleware::cors_middleware;
+
+mod csrf_middleware;
+pub use csrf_middleware::CsrfMiddleware;
+
+mod realm_middleware;
+pub use realm_middleware::RealmMiddleware;
+pub use realm_middleware::RealmPrefix;
+
+mod security_headers_middleware;
+pub use security_headers_middleware::security_headers_middleware;
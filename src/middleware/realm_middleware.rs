@@ -0,0 +1,94 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::future::Ready;
+use std::future::ready;
+
+use actix_web::HttpMessage;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::dev::forward_ready;
+use actix_web::http::Uri;
+use actix_web::http::uri::PathAndQuery;
+
+pub struct RealmPrefix(pub String);
+
+pub struct RealmMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RealmMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RealmMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RealmMiddlewareService { service }))
+    }
+}
+
+pub struct RealmMiddlewareService<S> {
+    service: S
+}
+
+impl<S> RealmMiddlewareService<S> {
+    fn split(path: &str) -> Option<(String, String)> {
+        let rest = path.strip_prefix("/realms/")?;
+
+        match rest.find('/') {
+            Some(index) => Some((rest[..index].to_string(), rest[index..].to_string())),
+            None => Some((rest.to_string(), "/".to_string()))
+        }
+    }
+
+    fn rewrite(request: &mut ServiceRequest, path: String) -> Option<()> {
+        let path_and_query = match request.query_string() {
+            "" => path,
+            query => format!("{}?{}", path, query)
+        };
+
+        let mut parts = request.head().uri.clone().into_parts();
+        parts.path_and_query = Some(path_and_query.parse::<PathAndQuery>().ok()?);
+
+        let uri = Uri::from_parts(parts).ok()?;
+
+        request.match_info_mut().get_mut().update(&uri);
+        request.head_mut().uri = uri;
+
+        Some(())
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RealmMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = S::Future;
+
+    forward_ready!(service);
+
+    fn call(&self, mut request: ServiceRequest) -> Self::Future {
+        if let Some((name, path)) = Self::split(request.path()) {
+            if Self::rewrite(&mut request, path).is_some() {
+                request.extensions_mut().insert(RealmPrefix(name));
+            }
+        }
+
+        self.service.call(request)
+    }
+}
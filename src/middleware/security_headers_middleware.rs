@@ -0,0 +1,32 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::http::header::REFERRER_POLICY;
+use actix_web::http::header::STRICT_TRANSPORT_SECURITY;
+use actix_web::http::header::X_CONTENT_TYPE_OPTIONS;
+use actix_web::http::header::X_FRAME_OPTIONS;
+use actix_web::middleware::DefaultHeaders;
+
+use dotenv_codegen::dotenv;
+
+pub fn security_headers_middleware() -> DefaultHeaders {
+    let hsts_max_age: u32 = dotenv!("HSTS_MAX_AGE").parse().expect("HSTS_MAX_AGE has to be a number of seconds");
+
+    let headers = DefaultHeaders::new()
+    .add((CACHE_CONTROL, "no-store"))
+    .add((REFERRER_POLICY, "no-referrer"))
+    .add((X_CONTENT_TYPE_OPTIONS, "nosniff"))
+    .add((X_FRAME_OPTIONS, "DENY"));
+
+    match hsts_max_age {
+        0 => headers,
+        _ => headers.add((STRICT_TRANSPORT_SECURITY, format!("max-age={}; includeSubDomains", hsts_max_age)))
+    }
+}
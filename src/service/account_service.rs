@@ -14,11 +14,34 @@ use sqlx::Pool;
 use sqlx::Postgres;
 use sqlx::postgres::PgConnectOptions;
 
+use validator::Validate;
+
 use crate::Error;
+use crate::dto::ImportDto;
+use crate::dto::ImportErrorDto;
+use crate::dto::ImportReportDto;
+use crate::entity::AccountStatus;
+use crate::entity::AuditAction;
+use crate::entity::AuditOutcome;
+use crate::entity::PasswordAlgorithm;
+use crate::entity::RealmEntity;
+use crate::extractor::ClientExtractor;
+use crate::service::CipherService;
+use crate::service::PasswordService;
 use crate::transaction::AccountTransaction;
 
 pub struct AccountService {
-    pool: Pool<Postgres>
+    pool: Pool<Postgres>,
+    password: PasswordService,
+    cipher: CipherService,
+    sudo_mode_window: i32,
+    new_device_notifications: bool,
+    last_seen_interval: i32,
+    impersonation_duration: i32,
+    deletion_grace_period: i32,
+    purge_interval: u64,
+    username_change_cooldown: i32,
+    username_reservation_period: i32
 }
 
 impl AccountService {
@@ -29,14 +52,183 @@ impl AccountService {
         .username(dotenv!("DATABASE_USER_NAME"))
         .password(dotenv!("DATABASE_USER_PASSWORD"));
 
-        AccountService { pool: PgPool::connect_lazy_with(options) }
+        AccountService {
+            pool: PgPool::connect_lazy_with(options),
+            password: PasswordService::new(),
+            cipher: CipherService::new(),
+            sudo_mode_window: dotenv!("SUDO_MODE_WINDOW").parse().expect("SUDO_MODE_WINDOW has to be a number of seconds"),
+            new_device_notifications: dotenv!("NEW_DEVICE_NOTIFICATIONS").parse().expect("NEW_DEVICE_NOTIFICATIONS has to be true or false"),
+            last_seen_interval: dotenv!("LAST_SEEN_INTERVAL").parse().expect("LAST_SEEN_INTERVAL has to be a number of seconds"),
+            impersonation_duration: dotenv!("IMPERSONATION_DURATION").parse().expect("IMPERSONATION_DURATION has to be a number of seconds"),
+            deletion_grace_period: dotenv!("DELETION_GRACE_PERIOD").parse().expect("DELETION_GRACE_PERIOD has to be a number of seconds"),
+            purge_interval: dotenv!("PURGE_INTERVAL").parse().expect("PURGE_INTERVAL has to be a number of seconds"),
+            username_change_cooldown: dotenv!("USERNAME_CHANGE_COOLDOWN").parse().expect("USERNAME_CHANGE_COOLDOWN has to be a number of seconds"),
+            username_reservation_period: dotenv!("USERNAME_RESERVATION_PERIOD").parse().expect("USERNAME_RESERVATION_PERIOD has to be a number of seconds")
+        }
+    }
+
+    pub fn sudo_mode_window(&self) -> i32 {
+        self.sudo_mode_window
+    }
+
+    pub fn new_device_notifications(&self) -> bool {
+        self.new_device_notifications
+    }
+
+    pub fn last_seen_interval(&self) -> i32 {
+        self.last_seen_interval
+    }
+
+    pub fn impersonation_duration(&self) -> i32 {
+        self.impersonation_duration
+    }
+
+    pub fn deletion_grace_period(&self) -> i32 {
+        self.deletion_grace_period
+    }
+
+    pub fn purge_interval(&self) -> u64 {
+        self.purge_interval
+    }
+
+    pub fn username_change_cooldown(&self) -> i32 {
+        self.username_change_cooldown
+    }
+
+    pub fn username_reservation_period(&self) -> i32 {
+        self.username_reservation_period
+    }
+
+    pub async fn find_realm(&self, name: Option<&str>, hostname: Option<&str>) -> Result<Option<RealmEntity>, Error> {
+        if let Some(name) = name {
+            return Ok(
+                sqlx::query_as::<_, RealmEntity>(r#"
+                    SELECT
+                        *
+                    FROM
+                        realm
+                    WHERE
+                        name = $1;
+                "#)
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?
+            );
+        }
+
+        Ok(
+            sqlx::query_as::<_, RealmEntity>(r#"
+                SELECT
+                    *
+                FROM
+                    realm
+                WHERE
+                    hostname = $1 OR
+                    name = 'default'
+                ORDER BY
+                    hostname IS NOT DISTINCT FROM $1 DESC
+                LIMIT
+                    1;
+            "#)
+            .bind(hostname)
+            .fetch_optional(&self.pool)
+            .await?
+        )
     }
 
-    pub async fn transaction<'t>(&'t self) -> Result<AccountTransaction<'t>, Error> {
+    pub async fn find_realms(&self) -> Result<Vec<RealmEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, RealmEntity>(r#"
+                SELECT
+                    *
+                FROM
+                    realm
+                ORDER BY
+                    id;
+            "#)
+            .fetch_all(&self.pool)
+            .await?
+        )
+    }
+
+    pub async fn transaction<'t>(&'t self, realm: &RealmEntity) -> Result<AccountTransaction<'t>, Error> {
         let transaction = self.pool.begin().await?;
         
         Ok(
-            AccountTransaction::new(transaction)
+            AccountTransaction::new(transaction, realm.id, &self.password, &self.cipher)
         )
     }
+
+    async fn import_row(transaction: &mut AccountTransaction<'_>, dto: &ImportDto, algorithm: PasswordAlgorithm, status: AccountStatus, actor_id: Option<i32>, client: &ClientExtractor) -> Result<(), Error> {
+        let entity = transaction.import(&dto.email, &dto.password_hash, algorithm, status).await?;
+
+        match actor_id {
+            Some(actor_id) => transaction.audit_as(actor_id, Some(entity.id), AuditAction::Import, AuditOutcome::Success, client).await,
+            None => transaction.audit(Some(entity.id), AuditAction::Import, AuditOutcome::Success, client).await
+        }
+    }
+
+    pub async fn import(&self, realm: &RealmEntity, rows: Vec<(u64, Result<ImportDto, String>)>, dry_run: bool, actor_id: Option<i32>, client: &ClientExtractor) -> Result<ImportReportDto, Error> {
+        let mut report = ImportReportDto { dry_run, imported: 0, errors: Vec::new() };
+        let mut transaction = self.transaction(realm).await?;
+
+        let status = match realm.email_verification {
+            true => AccountStatus::Unverified,
+            false => AccountStatus::Active
+        };
+
+        for (index, (line, row)) in rows.into_iter().enumerate() {
+            if !dry_run && index > 0 && index % 500 == 0 {
+                transaction.commit().await?;
+                transaction = self.transaction(realm).await?;
+            }
+
+            let dto = match row.and_then(|dto| dto.validate().map(|_| dto).map_err(|errors| errors.to_string())) {
+                Ok(dto) => dto,
+                Err(message) => {
+                    report.errors.push(ImportErrorDto { line, message });
+                    continue;
+                }
+            };
+
+            let algorithm = match PasswordService::identify(&dto.password_hash, dto.algorithm) {
+                Ok(algorithm) => algorithm,
+                Err(message) => {
+                    report.errors.push(ImportErrorDto { line, message: message.to_string() });
+                    continue;
+                }
+            };
+
+            if transaction.find_by_email(&dto.email).await?.is_some() {
+                report.errors.push(ImportErrorDto { line, message: "Account already exists".to_string() });
+                continue;
+            }
+
+            transaction.savepoint().await?;
+
+            match AccountService::import_row(&mut transaction, &dto, algorithm, status, actor_id, client).await {
+                Ok(()) => {
+                    transaction.release_savepoint().await?;
+                    report.imported += 1;
+                },
+                Err(error) => {
+                    transaction.rollback_to_savepoint().await?;
+                    report.errors.push(ImportErrorDto { line, message: error.to_string() });
+                }
+            }
+        }
+
+        match dry_run {
+            true => transaction.rollback().await?,
+            false => transaction.commit().await?
+        };
+
+        Ok(report)
+    }
+}
+
+impl Default for AccountService {
+    fn default() -> AccountService {
+        AccountService::new()
+    }
 }
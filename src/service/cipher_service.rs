@@ -0,0 +1,222 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::collections::HashMap;
+use std::env;
+
+use aes_gcm::Aes256Gcm;
+use aes_gcm::Key;
+use aes_gcm::KeyInit;
+use aes_gcm::Nonce;
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::AeadCore;
+use aes_gcm::aead::OsRng;
+
+use dotenv_codegen::dotenv;
+
+use hmac::Hmac;
+use hmac::Mac;
+
+use sha2::Sha256;
+
+use crate::Error;
+
+const NONCE_SIZE: usize = 12;
+const MIN_PREFIX_LENGTH: usize = 3;
+const MAX_PREFIX_LENGTH: usize = 8;
+
+pub struct CipherService {
+    keys: HashMap<String, Aes256Gcm>,
+    key_id: String,
+    index_key: Vec<u8>,
+    prefix_key: Vec<u8>,
+    provider_rules: bool
+}
+
+impl CipherService {
+    pub fn new() -> CipherService {
+        CipherService::with_keys(
+            &env::var("EMAIL_KEYS").unwrap_or_default(),
+            &env::var("EMAIL_KEY_ID").unwrap_or_default(),
+            &env::var("EMAIL_INDEX_KEY").unwrap_or_default(),
+            dotenv!("EMAIL_PROVIDER_RULES").parse().expect("EMAIL_PROVIDER_RULES has to be true or false")
+        )
+    }
+
+    pub fn with_keys(keys: &str, key_id: &str, index_key: &str, provider_rules: bool) -> CipherService {
+        let keys: HashMap<String, Aes256Gcm> = keys
+        .split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(|key| {
+            let (id, key) = key.split_once(':').expect("EMAIL_KEYS has to be a list of id:key pairs");
+            let key = hex::decode(key.trim()).expect("EMAIL_KEYS has to contain hex encoded keys");
+
+            assert!(key.len() == 32, "EMAIL_KEYS has to contain 256 bit keys");
+
+            (id.trim().to_string(), Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+        })
+        .collect();
+
+        let key_id = key_id.trim().to_string();
+
+        assert!(!keys.is_empty(), "EMAIL_KEYS must not be empty");
+        assert!(keys.contains_key(&key_id), "EMAIL_KEY_ID has to be one of the ids in EMAIL_KEYS");
+
+        let index_key = hex::decode(index_key.trim()).expect("EMAIL_INDEX_KEY has to be a hex encoded key");
+
+        assert!(index_key.len() == 32, "EMAIL_INDEX_KEY has to be a 256 bit key");
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&index_key).expect("HMAC accepts keys of any size");
+        mac.update(b"email prefix");
+
+        let prefix_key = mac.finalize().into_bytes().to_vec();
+
+        CipherService {
+            keys,
+            key_id,
+            index_key,
+            prefix_key,
+            provider_rules
+        }
+    }
+
+    pub fn key_id(&self) -> &String {
+        &self.key_id
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<(Vec<u8>, String), Error> {
+        self.encrypt_bytes(plaintext.as_bytes())
+    }
+
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<(Vec<u8>, String), Error> {
+        let cipher = &self.keys[&self.key_id];
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext).map_err(|_| Error::EncryptionError)?;
+
+        Ok(([nonce.as_slice(), &ciphertext].concat(), self.key_id.clone()))
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8], key_id: &str) -> Result<String, Error> {
+        String::from_utf8(self.decrypt_bytes(ciphertext, key_id)?).map_err(|_| Error::EncryptionError)
+    }
+
+    pub fn decrypt_bytes(&self, ciphertext: &[u8], key_id: &str) -> Result<Vec<u8>, Error> {
+        let cipher = self.keys.get(key_id).ok_or(Error::EncryptionError)?;
+
+        if ciphertext.len() < NONCE_SIZE {
+            return Err(Error::EncryptionError);
+        }
+
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_SIZE);
+
+        cipher.decrypt(Nonce::from_slice(nonce), ciphertext).map_err(|_| Error::EncryptionError)
+    }
+
+    pub fn blind_index(&self, plaintext: &str) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.index_key).expect("HMAC accepts keys of any size");
+        mac.update(plaintext.as_bytes());
+
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    pub fn canonical_email(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+
+        let (local, domain) = match email.rsplit_once('@') {
+            Some(parts) => parts,
+            None => {
+                return email;
+            }
+        };
+
+        if self.provider_rules && (domain == "gmail.com" || domain == "googlemail.com") {
+            let local = local.split('+').next().unwrap_or_default().replace('.', "");
+
+            return format!("{}@gmail.com", local);
+        }
+
+        email
+    }
+
+    pub fn email_index(&self, email: &str) -> String {
+        self.blind_index(&self.canonical_email(email))
+    }
+
+    fn local_part(email: &str) -> Vec<char> {
+        email.trim().to_lowercase().split('@').next().unwrap_or_default().chars().collect()
+    }
+
+    fn prefix_index(&self, prefix: &[char]) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.prefix_key).expect("HMAC accepts keys of any size");
+        mac.update(prefix.iter().collect::<String>().as_bytes());
+
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    pub fn email_prefix_index(&self, prefix: &str) -> String {
+        let local = CipherService::local_part(prefix);
+
+        self.prefix_index(&local[..local.len().min(MAX_PREFIX_LENGTH)])
+    }
+
+    pub fn email_prefix_indexes(&self, email: &str) -> Vec<String> {
+        let local = CipherService::local_part(email);
+
+        (local.len().min(MIN_PREFIX_LENGTH)..=local.len().min(MAX_PREFIX_LENGTH))
+        .map(|length| self.prefix_index(&local[..length]))
+        .collect()
+    }
+}
+
+impl Default for CipherService {
+    fn default() -> CipherService {
+        CipherService::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: &str = "1:000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const INDEX_KEY: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+    #[test]
+    fn canonical_email_ignores_case_and_whitespace() {
+        let cipher = CipherService::with_keys(KEYS, "1", INDEX_KEY, false);
+
+        assert_eq!(cipher.canonical_email(" Bob@Example.COM "), "bob@example.com");
+        assert_eq!(cipher.canonical_email("first.last+news@gmail.com"), "first.last+news@gmail.com");
+        assert_eq!(cipher.canonical_email("no-at-sign"), "no-at-sign");
+        assert_eq!(cipher.email_index("Bob@Example.com"), cipher.email_index("bob@example.com"));
+    }
+
+    #[test]
+    fn canonical_email_applies_provider_rules() {
+        let cipher = CipherService::with_keys(KEYS, "1", INDEX_KEY, true);
+
+        assert_eq!(cipher.canonical_email("First.Last+news@gmail.com"), "firstlast@gmail.com");
+        assert_eq!(cipher.canonical_email("firstlast@googlemail.com"), "firstlast@gmail.com");
+        assert_eq!(cipher.canonical_email("first.last+news@example.com"), "first.last+news@example.com");
+        assert_eq!(cipher.email_index("f.irst.last@gmail.com"), cipher.email_index("firstlast+x@googlemail.com"));
+    }
+
+    #[test]
+    fn email_prefix_indexes_cover_the_start_of_the_local_part() {
+        let cipher = CipherService::with_keys(KEYS, "1", INDEX_KEY, false);
+        let indexes = cipher.email_prefix_indexes("Christopher@example.com");
+
+        assert_eq!(indexes.len(), 6);
+        assert_eq!(indexes[0], cipher.email_prefix_index("chr"));
+        assert_eq!(indexes[5], cipher.email_prefix_index("christop"));
+        assert_eq!(cipher.email_prefix_index("Christopher@exa"), cipher.email_prefix_index("christop"));
+        assert_eq!(cipher.email_prefix_indexes("bo@example.com"), vec![cipher.email_prefix_index("bo@")]);
+        assert!(!indexes.contains(&cipher.blind_index("chr")));
+    }
+}
@@ -0,0 +1,90 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use dotenv_codegen::dotenv;
+
+use lettre::AsyncSmtpTransport;
+use lettre::AsyncTransport;
+use lettre::Message;
+use lettre::Tokio1Executor;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+
+use crate::Error;
+
+enum MailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    Log
+}
+
+pub struct MailService {
+    transport: MailTransport,
+    sender: String,
+    public_url: String
+}
+
+impl MailService {
+    pub fn new() -> MailService {
+        let transport = match dotenv!("MAIL_TRANSPORT") {
+            "smtp" => {
+                let port = dotenv!("SMTP_PORT").parse().expect("SMTP_PORT has to be a valid port");
+                let credentials = Credentials::new(
+                    dotenv!("SMTP_USER_NAME").to_string(),
+                    dotenv!("SMTP_USER_PASSWORD").to_string()
+                );
+
+                let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(dotenv!("SMTP_HOST"))
+                .expect("SMTP_HOST has to be a valid host")
+                .port(port)
+                .credentials(credentials)
+                .build();
+
+                MailTransport::Smtp(transport)
+            },
+            "log" => MailTransport::Log,
+            _ => panic!("MAIL_TRANSPORT has to be smtp or log")
+        };
+
+        MailService {
+            transport,
+            sender: dotenv!("MAIL_SENDER").to_string(),
+            public_url: dotenv!("PUBLIC_URL").to_string()
+        }
+    }
+
+    pub fn link(&self, path: &str, token: &String) -> String {
+        format!("{}{}?token={}", self.public_url, path, token)
+    }
+
+    pub async fn send(&self, to: &String, subject: &str, body: String) -> Result<(), Error> {
+        match &self.transport {
+            MailTransport::Smtp(transport) => {
+                let message = Message::builder()
+                .from(self.sender.parse()?)
+                .to(to.parse()?)
+                .subject(subject)
+                .header(ContentType::TEXT_PLAIN)
+                .body(body)?;
+
+                transport.send(message).await?;
+            },
+            MailTransport::Log => {
+                log::info!("Mail to {}: {}", to, subject);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MailService {
+    fn default() -> MailService {
+        MailService::new()
+    }
+}
@@ -9,4 +9,12 @@
  
 mod account_service;
 pub use account_service::AccountService;
- 
\ No newline at end of file
+
+mod cipher_service;
+pub use cipher_service::CipherService;
+
+mod mail_service;
+pub use mail_service::MailService;
+
+mod password_service;
+pub use password_service::PasswordService;
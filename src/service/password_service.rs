@@ -0,0 +1,399 @@
+/*******************************************************
+ * Copyright (C) 2023 - present, Blackwood Studio
+ *
+ * This file is part of the Auth Project.
+ *
+ * The Auth Project can not be copied and/or distributed without the express
+ * permission of an Blackwood Studio Admin
+ *******************************************************/
+
+use std::collections::HashMap;
+use std::env;
+
+use argon2::Argon2;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use bcrypt::DEFAULT_COST;
+use bcrypt::HashParts;
+use bcrypt::hash;
+use bcrypt::verify;
+
+use hmac::Hmac;
+use hmac::Mac;
+
+use password_hash::PasswordHash;
+
+use pbkdf2::Pbkdf2;
+use pbkdf2::pbkdf2_hmac;
+
+use scrypt::Scrypt;
+
+use sha1::Sha1;
+
+use sha2::Digest;
+use sha2::Sha256;
+use sha2::Sha512;
+
+use subtle::ConstantTimeEq;
+
+use crate::Error;
+use crate::entity::PasswordAlgorithm;
+
+type Pbkdf2Function = fn(&[u8], &[u8], u32, &mut [u8]);
+
+const MAX_BCRYPT_COST: u32 = 16;
+const MAX_PBKDF2_ITERATIONS: u32 = 2_000_000;
+const MAX_PBKDF2_LENGTH: u32 = 64;
+const MAX_ARGON2_MEMORY: u32 = 262_144;
+const MAX_ARGON2_ITERATIONS: u32 = 10;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+const MAX_SCRYPT_LOG_N: u32 = 17;
+const MAX_SCRYPT_BLOCK_SIZE: u32 = 16;
+const MAX_SCRYPT_PARALLELISM: u32 = 4;
+
+pub struct PasswordService {
+    peppers: HashMap<String, Vec<u8>>,
+    pepper_id: Option<String>
+}
+
+impl PasswordService {
+    pub fn new() -> PasswordService {
+        PasswordService::with_peppers(&env::var("PASSWORD_PEPPERS").unwrap_or_default(), &env::var("PASSWORD_PEPPER_ID").unwrap_or_default())
+    }
+
+    pub fn with_peppers(peppers: &str, pepper_id: &str) -> PasswordService {
+        let peppers: HashMap<String, Vec<u8>> = peppers
+        .split(',')
+        .filter(|pepper| !pepper.trim().is_empty())
+        .map(|pepper| {
+            let (id, secret) = pepper.split_once(':').expect("PASSWORD_PEPPERS has to be a list of id:secret pairs");
+            (id.trim().to_string(), secret.trim().as_bytes().to_vec())
+        })
+        .collect();
+
+        let pepper_id = match pepper_id.trim() {
+            "" => None,
+            pepper_id => {
+                assert!(peppers.contains_key(pepper_id), "PASSWORD_PEPPER_ID has to be one of the ids in PASSWORD_PEPPERS");
+                Some(pepper_id.to_string())
+            }
+        };
+
+        PasswordService { peppers, pepper_id }
+    }
+
+    fn pepper(&self, password: &String, pepper_id: &Option<String>) -> Result<String, Error> {
+        let pepper_id = match pepper_id {
+            Some(pepper_id) => pepper_id,
+            None => {
+                return Ok(password.clone());
+            }
+        };
+
+        let secret = self.peppers.get(pepper_id).ok_or(Error::HashingError)?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).map_err(|_| Error::HashingError)?;
+
+        mac.update(password.as_bytes());
+
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    pub fn hash(&self, password: &String) -> Result<(String, Option<String>), Error> {
+        let password_hash = hash(self.pepper(password, &self.pepper_id)?, DEFAULT_COST)?;
+        Ok((password_hash, self.pepper_id.clone()))
+    }
+
+    pub fn verify(&self, password: &String, password_hash: &str, pepper_id: &Option<String>) -> Result<bool, Error> {
+        Ok(verify(self.pepper(password, pepper_id)?, password_hash)?)
+    }
+
+    pub fn identify(password_hash: &str, algorithm: Option<PasswordAlgorithm>) -> Result<PasswordAlgorithm, &'static str> {
+        let detected = match password_hash {
+            password_hash if ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| password_hash.starts_with(prefix)) => Some(PasswordAlgorithm::Bcrypt),
+            password_hash if password_hash.starts_with("$argon2") => Some(PasswordAlgorithm::Argon2),
+            password_hash if password_hash.starts_with("$scrypt$") => Some(PasswordAlgorithm::Scrypt),
+            password_hash if password_hash.starts_with("$pbkdf2") => Some(PasswordAlgorithm::Pbkdf2),
+            password_hash if password_hash.starts_with("pbkdf2_") => Some(PasswordAlgorithm::Pbkdf2),
+            _ => None
+        };
+
+        let algorithm = algorithm.or(detected).ok_or("Unknown password hash format")?;
+
+        let digest_size = match algorithm {
+            PasswordAlgorithm::SaltedSha1 => 20,
+            PasswordAlgorithm::SaltedSha256 => 32,
+            PasswordAlgorithm::SaltedSha512 => 64,
+            _ if detected != Some(algorithm) => {
+                return Err("The password hash doesn't match the algorithm");
+            },
+            PasswordAlgorithm::Bcrypt => {
+                return match password_hash.parse::<HashParts>() {
+                    Ok(parts) if parts.get_cost() > MAX_BCRYPT_COST => Err("The cost of the password hash is too high"),
+                    Ok(_) => Ok(algorithm),
+                    Err(_) => Err("Invalid bcrypt hash")
+                };
+            },
+            PasswordAlgorithm::Pbkdf2 if password_hash.starts_with("pbkdf2_") => {
+                return match PasswordService::django_parts(password_hash) {
+                    Some(_) => Ok(algorithm),
+                    None => Err("Invalid Django PBKDF2 hash")
+                };
+            },
+            _ => {
+                return match PasswordHash::new(password_hash) {
+                    Ok(password_hash) if !PasswordService::within_limits(&password_hash) => Err("The cost of the password hash is too high"),
+                    Ok(_) => Ok(algorithm),
+                    Err(_) => Err("Invalid PHC string")
+                };
+            }
+        };
+
+        let digest = password_hash.rsplit_once('$').and_then(|(_, digest)| hex::decode(digest).ok());
+
+        match digest {
+            Some(digest) if digest.len() == digest_size => Ok(algorithm),
+            _ => Err("Salted SHA hashes have to be formatted as salt$hex_digest")
+        }
+    }
+
+    pub fn matches(&self, password: &String, password_hash: &str, pepper_id: &Option<String>, algorithm: PasswordAlgorithm) -> Result<bool, Error> {
+        match algorithm {
+            PasswordAlgorithm::Bcrypt => self.verify(password, password_hash, pepper_id),
+            algorithm => Ok(PasswordService::verify_legacy(password, password_hash, algorithm))
+        }
+    }
+
+    pub fn verify_legacy(password: &String, password_hash: &str, algorithm: PasswordAlgorithm) -> bool {
+        match algorithm {
+            PasswordAlgorithm::Bcrypt => verify(password, password_hash).unwrap_or(false),
+            PasswordAlgorithm::Pbkdf2 if password_hash.starts_with("pbkdf2_") => PasswordService::verify_django(password, password_hash),
+            PasswordAlgorithm::Argon2 | PasswordAlgorithm::Scrypt | PasswordAlgorithm::Pbkdf2 => match PasswordHash::new(password_hash) {
+                Ok(password_hash) if PasswordService::within_limits(&password_hash) => password_hash.verify_password(&[&Argon2::default(), &Scrypt, &Pbkdf2], password).is_ok(),
+                _ => false
+            },
+            PasswordAlgorithm::SaltedSha1 => PasswordService::verify_salted::<Sha1>(password, password_hash),
+            PasswordAlgorithm::SaltedSha256 => PasswordService::verify_salted::<Sha256>(password, password_hash),
+            PasswordAlgorithm::SaltedSha512 => PasswordService::verify_salted::<Sha512>(password, password_hash)
+        }
+    }
+
+    fn within_limits(password_hash: &PasswordHash) -> bool {
+        let limits: &[(&str, u32)] = match password_hash.algorithm.as_str() {
+            "argon2d" | "argon2i" | "argon2id" => &[("m", MAX_ARGON2_MEMORY), ("t", MAX_ARGON2_ITERATIONS), ("p", MAX_ARGON2_PARALLELISM)],
+            "scrypt" => &[("ln", MAX_SCRYPT_LOG_N), ("r", MAX_SCRYPT_BLOCK_SIZE), ("p", MAX_SCRYPT_PARALLELISM)],
+            algorithm if algorithm.starts_with("pbkdf2") => &[("i", MAX_PBKDF2_ITERATIONS), ("l", MAX_PBKDF2_LENGTH)],
+            _ => {
+                return false;
+            }
+        };
+
+        limits.iter().all(|(name, limit)| match password_hash.params.get_decimal(*name) {
+            Some(value) => value <= *limit,
+            None => password_hash.params.get(*name).is_none()
+        })
+    }
+
+    fn verify_salted<D: Digest>(password: &String, password_hash: &str) -> bool {
+        let (salt, digest) = match password_hash.rsplit_once('$') {
+            Some(parts) => parts,
+            None => {
+                return false;
+            }
+        };
+
+        let mut hasher = D::new();
+
+        hasher.update(salt.as_bytes());
+        hasher.update(password.as_bytes());
+
+        let digest = match hex::decode(digest) {
+            Ok(digest) => digest,
+            Err(_) => {
+                return false;
+            }
+        };
+
+        hasher.finalize().as_slice().ct_eq(&digest).into()
+    }
+
+    fn django_parts(password_hash: &str) -> Option<(Pbkdf2Function, u32, &str, Vec<u8>)> {
+        let mut parts = password_hash.split('$');
+
+        let (function, size): (Pbkdf2Function, usize) = match parts.next()? {
+            "pbkdf2_sha1" => (pbkdf2_hmac::<Sha1>, 20),
+            "pbkdf2_sha256" => (pbkdf2_hmac::<Sha256>, 32),
+            _ => {
+                return None;
+            }
+        };
+
+        let iterations = parts.next()?.parse().ok()?;
+        let salt = parts.next()?;
+        let digest = STANDARD.decode(parts.next()?).ok()?;
+
+        if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS || digest.len() != size {
+            return None;
+        }
+
+        match parts.next() {
+            Some(_) => None,
+            None => Some((function, iterations, salt, digest))
+        }
+    }
+
+    fn verify_django(password: &String, password_hash: &str) -> bool {
+        let (function, iterations, salt, digest) = match PasswordService::django_parts(password_hash) {
+            Some(parts) => parts,
+            None => {
+                return false;
+            }
+        };
+
+        let mut derived = vec![0; digest.len()];
+
+        function(password.as_bytes(), salt.as_bytes(), iterations, &mut derived);
+
+        derived.ct_eq(&digest).into()
+    }
+
+    pub fn is_current(&self, pepper_id: &Option<String>) -> bool {
+        *pepper_id == self.pepper_id
+    }
+}
+
+impl Default for PasswordService {
+    fn default() -> PasswordService {
+        PasswordService::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "Secret#Pass1";
+
+    const ARGON2: &str = "$argon2id$v=19$m=1024,t=1,p=1$c29tZXNhbHRzYWx0$wWWbKgrg+4SlLjMNp4PTR61fKAUDYksrtc5Z8VvYhcs";
+    const SCRYPT: &str = "$scrypt$ln=10,r=8,p=1$c29tZXNhbHRzYWx0$0ZGq9RvCjCuTga2xhyJVDQeN9NwBJ+ne/1IGC2hVVyo";
+    const PBKDF2: &str = "$pbkdf2-sha256$i=1000,l=32$c29tZXNhbHRzYWx0$GQC/7XjFpNnpJfvy1qMrb1M73LohxBix4H/gdneIoCs";
+    const BCRYPT: &str = "$2b$04$a0DqbFLfZFPxWUvya0Dqb.1ozGELGTbb.gxjiH8Rp7tN1NCY.v/xe";
+    const DJANGO_SHA1: &str = "pbkdf2_sha1$1000$somesalt$WBUQuZYLSkyWv3wmTb27i2h7T3g=";
+    const DJANGO_SHA256: &str = "pbkdf2_sha256$1000$somesalt$Cx154gnFqtzG+pmkmSoS6aLnzafEJtrgTKcIqMc1cFo=";
+    const SALTED_SHA1: &str = "somesalt$cb612726374d7378d90f4dc48323bf2c86ccd8ad";
+    const SALTED_SHA256: &str = "somesalt$c2bf07b3b93f4a70f3b125d04d890e5f5796a6840675e1702a8a2ad21607dbb4";
+    const SALTED_SHA512: &str = "somesalt$388ae4fed302143499401aa23d8ecb3391797927061fb47fb03a22c70f945acd6d67f6b12d30fc031a121ed0ec5b30abaac7166155014701acda35ce1f9b3d8a";
+
+    fn vectors() -> [(&'static str, Option<PasswordAlgorithm>, PasswordAlgorithm); 9] {
+        [
+            (ARGON2, None, PasswordAlgorithm::Argon2),
+            (SCRYPT, None, PasswordAlgorithm::Scrypt),
+            (PBKDF2, None, PasswordAlgorithm::Pbkdf2),
+            (BCRYPT, None, PasswordAlgorithm::Bcrypt),
+            (DJANGO_SHA1, None, PasswordAlgorithm::Pbkdf2),
+            (DJANGO_SHA256, None, PasswordAlgorithm::Pbkdf2),
+            (SALTED_SHA1, Some(PasswordAlgorithm::SaltedSha1), PasswordAlgorithm::SaltedSha1),
+            (SALTED_SHA256, Some(PasswordAlgorithm::SaltedSha256), PasswordAlgorithm::SaltedSha256),
+            (SALTED_SHA512, Some(PasswordAlgorithm::SaltedSha512), PasswordAlgorithm::SaltedSha512)
+        ]
+    }
+
+    #[test]
+    fn identifies_legacy_hashes() {
+        for (password_hash, algorithm, expected) in vectors() {
+            assert!(PasswordService::identify(password_hash, algorithm) == Ok(expected), "{}", password_hash);
+        }
+    }
+
+    #[test]
+    fn verifies_legacy_hashes() {
+        let password = PASSWORD.to_string();
+        let wrong = "Wrong#Pass1".to_string();
+
+        for (password_hash, _, algorithm) in vectors() {
+            assert!(PasswordService::verify_legacy(&password, password_hash, algorithm), "{}", password_hash);
+            assert!(!PasswordService::verify_legacy(&wrong, password_hash, algorithm), "{}", password_hash);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_legacy_hashes() {
+        let password_hashes = [
+            ("pbkdf2_sha256$0$somesalt$Cx154gnFqtzG+pmkmSoS6aLnzafEJtrgTKcIqMc1cFo=", None),
+            ("pbkdf2_sha256$1000$somesalt$", None),
+            ("pbkdf2_sha256$1000$somesalt$WBUQuZYLSkyWv3wmTb27i2h7T3g=", None),
+            ("pbkdf2_sha256$1000$somesalt$Cx154gnFqtzG+pmkmSoS6aLnzafEJtrgTKcIqMc1cFo=$", None),
+            ("somesalt$cb612726374d7378d90f4dc48323bf2c86ccd8ad", Some(PasswordAlgorithm::SaltedSha256)),
+            ("somesalt$zz612726374d7378d90f4dc48323bf2c86ccd8ad", Some(PasswordAlgorithm::SaltedSha1)),
+            ("somesalt", Some(PasswordAlgorithm::SaltedSha1)),
+            ("$1$somesalt$digest", None)
+        ];
+
+        for (password_hash, algorithm) in password_hashes {
+            assert!(PasswordService::identify(password_hash, algorithm).is_err(), "{}", password_hash);
+        }
+    }
+
+    #[test]
+    fn rejects_legacy_hashes_above_the_cost_limits() {
+        let password = PASSWORD.to_string();
+
+        let password_hashes = [
+            ("pbkdf2_sha256$4000000000$somesalt$Cx154gnFqtzG+pmkmSoS6aLnzafEJtrgTKcIqMc1cFo=", PasswordAlgorithm::Pbkdf2),
+            ("$pbkdf2-sha256$i=4000000000,l=32$c29tZXNhbHRzYWx0$GQC/7XjFpNnpJfvy1qMrb1M73LohxBix4H/gdneIoCs", PasswordAlgorithm::Pbkdf2),
+            ("$pbkdf2-sha256$i=1000,l=100000$c29tZXNhbHRzYWx0$GQC/7XjFpNnpJfvy1qMrb1M73LohxBix4H/gdneIoCs", PasswordAlgorithm::Pbkdf2),
+            ("$argon2id$v=19$m=4194304,t=1,p=1$c29tZXNhbHRzYWx0$wWWbKgrg+4SlLjMNp4PTR61fKAUDYksrtc5Z8VvYhcs", PasswordAlgorithm::Argon2),
+            ("$argon2id$v=19$m=1024,t=1000,p=1$c29tZXNhbHRzYWx0$wWWbKgrg+4SlLjMNp4PTR61fKAUDYksrtc5Z8VvYhcs", PasswordAlgorithm::Argon2),
+            ("$argon2id$v=19$m=1024,t=1,p=255$c29tZXNhbHRzYWx0$wWWbKgrg+4SlLjMNp4PTR61fKAUDYksrtc5Z8VvYhcs", PasswordAlgorithm::Argon2),
+            ("$scrypt$ln=30,r=8,p=1$c29tZXNhbHRzYWx0$0ZGq9RvCjCuTga2xhyJVDQeN9NwBJ+ne/1IGC2hVVyo", PasswordAlgorithm::Scrypt),
+            ("$scrypt$ln=10,r=1024,p=1$c29tZXNhbHRzYWx0$0ZGq9RvCjCuTga2xhyJVDQeN9NwBJ+ne/1IGC2hVVyo", PasswordAlgorithm::Scrypt),
+            ("$scrypt$ln=10,r=8,p=64$c29tZXNhbHRzYWx0$0ZGq9RvCjCuTga2xhyJVDQeN9NwBJ+ne/1IGC2hVVyo", PasswordAlgorithm::Scrypt)
+        ];
+
+        for (password_hash, algorithm) in password_hashes {
+            assert!(PasswordService::identify(password_hash, None).is_err(), "{}", password_hash);
+            assert!(!PasswordService::verify_legacy(&password, password_hash, algorithm), "{}", password_hash);
+        }
+
+        assert!(PasswordService::identify("$2b$31$a0DqbFLfZFPxWUvya0Dqb.1ozGELGTbb.gxjiH8Rp7tN1NCY.v/xe", None).is_err());
+    }
+
+    #[test]
+    fn hashes_with_the_current_pepper() {
+        let service = PasswordService::with_peppers("p1:secret,p2:other", "p2");
+        let password = PASSWORD.to_string();
+        let (password_hash, pepper_id) = service.hash(&password).unwrap();
+
+        assert_eq!(pepper_id, Some("p2".to_string()));
+        assert!(service.verify(&password, &password_hash, &pepper_id).unwrap());
+        assert!(!service.verify(&password, &password_hash, &Some("p1".to_string())).unwrap());
+        assert!(!service.verify(&password, &password_hash, &None).unwrap());
+        assert!(!service.verify(&"Wrong#Pass1".to_string(), &password_hash, &pepper_id).unwrap());
+        assert!(service.verify(&password, &password_hash, &Some("p3".to_string())).is_err());
+    }
+
+    #[test]
+    fn rehashes_after_a_pepper_rotation() {
+        let previous = PasswordService::with_peppers("p1:secret", "p1");
+        let service = PasswordService::with_peppers("p1:secret,p2:other", "p2");
+        let password = PASSWORD.to_string();
+        let (password_hash, pepper_id) = previous.hash(&password).unwrap();
+
+        assert!(service.verify(&password, &password_hash, &pepper_id).unwrap());
+        assert!(!service.is_current(&pepper_id));
+        assert!(service.is_current(&Some("p2".to_string())));
+        assert!(!service.is_current(&None));
+    }
+
+    #[test]
+    fn hashes_without_a_pepper() {
+        let service = PasswordService::with_peppers("", "");
+        let password = PASSWORD.to_string();
+
+        assert!(service.verify(&password, BCRYPT, &None).unwrap());
+        assert!(service.is_current(&None));
+        assert!(!service.is_current(&Some("p1".to_string())));
+        assert_eq!(service.hash(&password).unwrap().1, None);
+    }
+}
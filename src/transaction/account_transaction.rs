@@ -10,21 +10,80 @@
 use bcrypt::DEFAULT_COST;
 use bcrypt::hash;
  
+use chrono::DateTime;
+use chrono::Utc;
+
+use rand::Rng;
+
 use sqlx::Postgres;
 use sqlx::Transaction;
  
+use sha2::Digest;
+use sha2::Sha256;
+
+use serde_json::Value;
+
 use uuid::Uuid;
  
 use crate::Error;
+use crate::dto::normalize_email;
+use crate::extractor::ClientExtractor;
+use crate::service::CipherService;
+use crate::service::PasswordService;
 use crate::entity::AccountEntity; 
+use crate::entity::AccountStatus;
+use crate::entity::AuditAction;
+use crate::entity::AuditEventEntity;
+use crate::entity::AuditOutcome;
+use crate::entity::DeviceEntity;
+use crate::entity::EmailChangeEntity;
+use crate::entity::ExportEntity;
+use crate::entity::ExportStatus;
+use crate::entity::InvitationEntity;
+use crate::entity::LoginCodeEntity;
+use crate::entity::MembershipEntity;
+use crate::entity::OrganizationEntity;
+use crate::entity::OrganizationRole;
+use crate::entity::PasswordAlgorithm;
+use crate::entity::PasswordHistoryEntity;
+use crate::entity::SessionEntity;
 
 pub struct AccountTransaction<'t> {
-    transaction: Transaction<'t, Postgres>
+    transaction: Transaction<'t, Postgres>,
+    realm_id: i32,
+    password: &'t PasswordService,
+    cipher: &'t CipherService,
+    impersonator_id: Option<i32>
 }
 
 impl<'t> AccountTransaction<'t> {
-    pub fn new(transaction: Transaction<'t, Postgres>) -> AccountTransaction<'t> {
-        AccountTransaction { transaction }
+    pub fn new(transaction: Transaction<'t, Postgres>, realm_id: i32, password: &'t PasswordService, cipher: &'t CipherService) -> AccountTransaction<'t> {
+        AccountTransaction { transaction, realm_id, password, cipher, impersonator_id: None }
+    }
+
+    fn decrypt_account(&self, mut entity: AccountEntity) -> Result<AccountEntity, Error> {
+        entity.email = match (&entity.email_ciphertext, &entity.email_key_id, &entity.legacy_email) {
+            (Some(email_ciphertext), Some(email_key_id), _) => self.cipher.decrypt(email_ciphertext, email_key_id)?,
+            (_, _, Some(legacy_email)) => legacy_email.clone(),
+            _ => {
+                return Err(Error::EncryptionError);
+            }
+        };
+
+        Ok(entity)
+    }
+
+    fn decrypt_invitation(&self, mut entity: InvitationEntity) -> Result<InvitationEntity, Error> {
+        entity.email = self.cipher.decrypt(&entity.email_ciphertext, &entity.email_key_id)?;
+
+        Ok(entity)
+    }
+
+    fn decrypt_email_change(&self, mut entity: EmailChangeEntity) -> Result<EmailChangeEntity, Error> {
+        entity.old_email = self.cipher.decrypt(&entity.old_email_ciphertext, &entity.email_key_id)?;
+        entity.new_email = self.cipher.decrypt(&entity.new_email_ciphertext, &entity.email_key_id)?;
+
+        Ok(entity)
     }
 
     pub async fn lock(&mut self) -> Result<(), Error> {
@@ -40,137 +99,2299 @@ impl<'t> AccountTransaction<'t> {
     }
     
     pub async fn find_by_email(&mut self, email: &String) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                realm_id = $1 AND
+                (email_index IN ($2, $3) OR (email_index IS NULL AND LOWER(email) = LOWER($4)));
+        "#)
+        .bind(self.realm_id)
+        .bind(self.cipher.email_index(email))
+        .bind(self.cipher.blind_index(email))
+        .bind(email)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn find_by_username(&mut self, username: &str) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                realm_id = $1 AND
+                LOWER(username) = LOWER($2);
+        "#)
+        .bind(self.realm_id)
+        .bind(username)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn username_available(&mut self, username: &str, account_id: Option<i32>) -> Result<bool, Error> {
         Ok(
-            sqlx::query_as::<_, AccountEntity>(r#"
-                SELECT
-                    *
-                FROM
-                    account
-                WHERE
-                    email = $1;
+            sqlx::query_scalar::<_, bool>(r#"
+                SELECT NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        account
+                    WHERE
+                        realm_id = $1 AND
+                        LOWER(username) = LOWER($2) AND
+                        id IS DISTINCT FROM $3
+                ) AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        username_reservation
+                    WHERE
+                        realm_id = $1 AND
+                        username = LOWER($2) AND
+                        account_id IS DISTINCT FROM $3 AND
+                        expires_at > NOW()
+                );
             "#)
-            .bind(email)
-            .fetch_optional(&mut *self.transaction)
+            .bind(self.realm_id)
+            .bind(username)
+            .bind(account_id)
+            .fetch_one(&mut *self.transaction)
             .await?
         )
     }
 
-    pub async fn find_by_write_key(&mut self, write_key: &String) -> Result<Option<AccountEntity>, Error> {
-        Ok(
-            sqlx::query_as::<_, AccountEntity>(r#"
-                SELECT
-                    *
-                FROM
-                    account
-                WHERE
-                    write_key = $1;
+    pub async fn update_username(&mut self, entity: &AccountEntity, username: &str, reservation_period: i32) -> Result<(), Error> {
+        if let Some(old_username) = entity.username.as_ref().filter(|old_username| !old_username.eq_ignore_ascii_case(username)) {
+            sqlx::query(r#"
+                INSERT INTO
+                    username_reservation(realm_id, username, account_id, expires_at)
+                VALUES
+                    ($1, LOWER($2), $3, NOW() + $4 * INTERVAL '1 second')
+                ON CONFLICT (realm_id, username) DO UPDATE SET
+                    account_id = EXCLUDED.account_id,
+                    expires_at = EXCLUDED.expires_at;
             "#)
-            .bind(write_key)
-            .fetch_optional(&mut *self.transaction)
-            .await?
-        )
+            .bind(self.realm_id)
+            .bind(old_username)
+            .bind(entity.id)
+            .bind(reservation_period)
+            .execute(&mut *self.transaction)
+            .await?;
+        }
+
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                username = $1,
+                username_changed_at = CASE WHEN username IS NULL THEN username_changed_at ELSE NOW() END,
+                updated_at = NOW()
+            WHERE
+                id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(username)
+        .bind(entity.id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_expired_username_reservations(&mut self) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                username_reservation
+            WHERE
+                expires_at < NOW() AND
+                realm_id = $1;
+        "#)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub fn same_email(&self, email: &str, other: &str) -> bool {
+        self.cipher.canonical_email(email) == self.cipher.canonical_email(other)
+    }
+
+    pub async fn find_by_id(&mut self, id: i32) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn find_by_public_id(&mut self, public_id: &Uuid) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                public_id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(public_id)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn find_by_write_key(&mut self, write_key: &String) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                account.*,
+                session.impersonator_id
+            FROM
+                account
+            INNER JOIN
+                session ON session.account_id = account.id
+            WHERE
+                session.write_key = $1 AND
+                session.realm_id = $2 AND
+                (session.expires_at IS NULL OR session.expires_at > NOW()) AND
+                account.status = 'active';
+        "#)
+        .bind(write_key)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        if let Some(entity) = &entity {
+            self.impersonator_id = entity.impersonator_id;
+        }
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
     }
 
     pub async fn find_by_read_key(&mut self, read_key: &String) -> Result<Option<AccountEntity>, Error> {
-        Ok(
-            sqlx::query_as::<_, AccountEntity>(r#"
-                SELECT
-                    *
-                FROM
-                    account
-                WHERE
-                    read_key = $1;
-            "#)
-            .bind(read_key)
-            .fetch_optional(&mut *self.transaction)
-            .await?
-        )
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                account.*,
+                session.impersonator_id
+            FROM
+                account
+            INNER JOIN
+                session ON session.account_id = account.id
+            WHERE
+                session.read_key = $1 AND
+                session.realm_id = $2 AND
+                (session.expires_at IS NULL OR session.expires_at > NOW()) AND
+                account.status = 'active';
+        "#)
+        .bind(read_key)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        if let Some(entity) = &entity {
+            self.impersonator_id = entity.impersonator_id;
+        }
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
     }
 
-    pub async fn create(&mut self, email: &String, password: &String) -> Result<AccountEntity, Error> {
-        let password_hash = hash(password, DEFAULT_COST)?;
-        let write_key = Uuid::new_v4().to_string();
-        let read_key = Uuid::new_v4().to_string();
+    pub async fn create(&mut self, email: &str, password: &String, status: AccountStatus) -> Result<AccountEntity, Error> {
+        let (password_hash, password_pepper_id) = self.password.hash(password)?;
+        let (email_ciphertext, email_key_id) = self.cipher.encrypt(email)?;
         
-        Ok(
-            sqlx::query_as::<_, AccountEntity>(r#"
-                INSERT INTO
-                    account(
-                        email,
-                        password_hash,
-                        write_key,
-                        read_key
-                    )
-                VALUES
-                    ($1,$2,$3,$4)
-                RETURNING 
-                    id,
-                    email,
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            INSERT INTO
+                account(
+                    realm_id,
+                    email_ciphertext,
+                    email_key_id,
+                    email_index,
                     password_hash,
-                    write_key,
-                    read_key;
-            "#)
-            .bind(email)
-            .bind(password_hash)
-            .bind(write_key)
-            .bind(read_key)
-            .fetch_one(&mut *self.transaction)
-            .await?
-        )
+                    password_pepper_id,
+                    status,
+                    public_id,
+                    password_changed_at
+                )
+            VALUES
+                ($1,$2,$3,$4,$5,$6,$7,$8,NOW())
+            RETURNING 
+                *;
+        "#)
+        .bind(self.realm_id)
+        .bind(email_ciphertext)
+        .bind(email_key_id)
+        .bind(self.cipher.email_index(email))
+        .bind(password_hash)
+        .bind(password_pepper_id)
+        .bind(status)
+        .bind(Uuid::now_v7())
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        self.store_email_prefixes(entity.id, email).await?;
+        self.decrypt_account(entity)
+    }
+
+    pub async fn import(&mut self, email: &str, password_hash: &str, password_algorithm: PasswordAlgorithm, status: AccountStatus) -> Result<AccountEntity, Error> {
+        let (email_ciphertext, email_key_id) = self.cipher.encrypt(email)?;
+
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            INSERT INTO
+                account(
+                    realm_id,
+                    email_ciphertext,
+                    email_key_id,
+                    email_index,
+                    password_hash,
+                    password_algorithm,
+                    status,
+                    public_id,
+                    password_changed_at
+                )
+            VALUES
+                ($1,$2,$3,$4,$5,$6,$7,$8,NOW())
+            RETURNING 
+                *;
+        "#)
+        .bind(self.realm_id)
+        .bind(email_ciphertext)
+        .bind(email_key_id)
+        .bind(self.cipher.email_index(email))
+        .bind(password_hash)
+        .bind(password_algorithm)
+        .bind(status)
+        .bind(Uuid::now_v7())
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        self.store_email_prefixes(entity.id, email).await?;
+        self.decrypt_account(entity)
     }
 
-    pub async fn update(&mut self, write_key: &String, email: &Option<String>, password: &Option<String>) -> Result<(), Error> {
+    pub async fn update(&mut self, id: i32, email: &Option<String>, password: &Option<String>) -> Result<(), Error> {
         if let Some(email) = email {
-            sqlx::query(r#"
-                UPDATE
-                    account
-                SET 
-                    email = $1
-                WHERE
-                    write_key = $2;
-                "#)
-            .bind(email)
-            .bind(write_key)
-            .execute(&mut *self.transaction)
-            .await?;
+            self.store_email(id, email, true).await?;
         }
 
         if let Some(password) = password {
-            let password_hash = hash(password, DEFAULT_COST)?;
+            self.store_password(id, password, true).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn store_email(&mut self, id: i32, email: &str, changed: bool) -> Result<(), Error> {
+        let (email_ciphertext, email_key_id) = self.cipher.encrypt(email)?;
+
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET 
+                email = NULL,
+                email_ciphertext = $1,
+                email_key_id = $2,
+                email_index = $3,
+                updated_at = CASE WHEN $4 THEN NOW() ELSE updated_at END
+            WHERE
+                id = $5 AND
+                realm_id = $6;
+            "#)
+        .bind(email_ciphertext)
+        .bind(email_key_id)
+        .bind(self.cipher.email_index(email))
+        .bind(changed)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
 
+        self.store_email_prefixes(id, email).await
+    }
+
+    async fn store_email_prefixes(&mut self, id: i32, email: &str) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                account_email_prefix
+            WHERE
+                account_id = $1;
+        "#)
+        .bind(id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            INSERT INTO
+                account_email_prefix(
+                    account_id,
+                    prefix_index
+                )
+            SELECT
+                $1,
+                UNNEST($2::VARCHAR[])
+            ON CONFLICT DO NOTHING;
+        "#)
+        .bind(id)
+        .bind(self.cipher.email_prefix_indexes(email))
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn store_password(&mut self, id: i32, password: &String, changed: bool) -> Result<(), Error> {
+        let (password_hash, password_pepper_id) = self.password.hash(password)?;
+
+        if changed {
             sqlx::query(r#"
-                UPDATE
+                INSERT INTO
+                    password_history(
+                        account_id,
+                        password_hash,
+                        password_pepper_id,
+                        password_algorithm
+                    )
+                SELECT
+                    id,
+                    password_hash,
+                    password_pepper_id,
+                    password_algorithm
+                FROM
                     account
-                SET
-                    password_hash = $1
                 WHERE
-                    write_key = $2;
+                    id = $1 AND
+                    realm_id = $2;
             "#)
-            .bind(password_hash)
-            .bind(write_key)
+            .bind(id)
+            .bind(self.realm_id)
             .execute(&mut *self.transaction)
             .await?;
         }
 
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                password_hash = $1,
+                password_pepper_id = $2,
+                password_algorithm = 'bcrypt',
+                password_changed_at = CASE WHEN $3 THEN NOW() ELSE password_changed_at END,
+                password_change_required = CASE WHEN $3 THEN FALSE ELSE password_change_required END,
+                updated_at = CASE WHEN $3 THEN NOW() ELSE updated_at END
+            WHERE
+                id = $4 AND
+                realm_id = $5;
+        "#)
+        .bind(password_hash)
+        .bind(password_pepper_id)
+        .bind(changed)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_profile(&mut self, id: i32, display_name: &Option<String>, avatar_url: &Option<String>, locale: &Option<String>, timezone: &Option<String>, attributes: &Value) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                display_name = $1,
+                avatar_url = $2,
+                locale = $3,
+                timezone = $4,
+                attributes = $5,
+                updated_at = NOW()
+            WHERE
+                id = $6 AND
+                realm_id = $7;
+        "#)
+        .bind(display_name)
+        .bind(avatar_url)
+        .bind(locale)
+        .bind(timezone)
+        .bind(attributes)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
         Ok(())
     }
 
-    pub async fn delete(&mut self, write_key: &String) -> Result<(), Error> {
+    pub async fn verify_password(&mut self, entity: &AccountEntity, password: &String) -> Result<bool, Error> {
+        if !self.password.matches(password, &entity.password_hash, &entity.password_pepper_id, entity.password_algorithm)? {
+            return Ok(false);
+        }
+
+        if entity.password_algorithm != PasswordAlgorithm::Bcrypt || !self.password.is_current(&entity.password_pepper_id) {
+            self.store_password(entity.id, password, false).await?;
+        }
+
+        Ok(true)
+    }
+
+    pub async fn password_reused(&mut self, entity: &AccountEntity, password: &String, depth: i32) -> Result<bool, Error> {
+        if self.password.matches(password, &entity.password_hash, &entity.password_pepper_id, entity.password_algorithm)? {
+            return Ok(true);
+        }
+
+        let entities = sqlx::query_as::<_, PasswordHistoryEntity>(r#"
+            SELECT
+                *
+            FROM
+                password_history
+            WHERE
+                account_id = $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $3)
+            ORDER BY
+                id DESC
+            LIMIT
+                $2;
+        "#)
+        .bind(entity.id)
+        .bind(depth as i64)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        for history in entities {
+            if self.password.matches(password, &history.password_hash, &history.password_pepper_id, history.password_algorithm)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub async fn delete_old_password_history(&mut self, depth: i32) -> Result<(), Error> {
         sqlx::query(r#"
             DELETE FROM
+                password_history
+            WHERE
+                id IN (
+                    SELECT
+                        id
+                    FROM (
+                        SELECT
+                            password_history.id,
+                            ROW_NUMBER() OVER (PARTITION BY password_history.account_id ORDER BY password_history.id DESC) AS position
+                        FROM
+                            password_history
+                        JOIN
+                            account ON account.id = password_history.account_id
+                        WHERE
+                            account.realm_id = $1
+                    ) AS history
+                    WHERE
+                        position > $2
+                );
+        "#)
+        .bind(self.realm_id)
+        .bind(depth as i64)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn activate(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
                 account
+            SET
+                status = $1,
+                suspended_reason = NULL,
+                suspended_until = NULL,
+                deletion_requested_at = NULL,
+                updated_at = NOW()
             WHERE
-                write_key = $1;
+                id = $2 AND
+                realm_id = $3;
         "#)
-        .bind(write_key)
+        .bind(AccountStatus::Active)
+        .bind(id)
+        .bind(self.realm_id)
         .execute(&mut *self.transaction)
         .await?;
 
         Ok(())
     }
 
-    pub async fn commit(self) -> Result<(), Error> {
-        self.transaction.commit().await?;
+    pub async fn suspend(&mut self, id: i32, reason: &Option<String>, until: &Option<DateTime<Utc>>) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                status = $1,
+                suspended_reason = $2,
+                suspended_until = $3,
+                updated_at = NOW()
+            WHERE
+                id = $4 AND
+                realm_id = $5;
+        "#)
+        .bind(AccountStatus::Suspended)
+        .bind(reason)
+        .bind(until)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn request_deletion(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                status = $1,
+                deletion_requested_at = NOW(),
+                updated_at = NOW()
+            WHERE
+                id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(AccountStatus::PendingDeletion)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_activity(&mut self, entity: &AccountEntity, interval: i32) -> Result<(), Error> {
+        if let Some(last_seen_at) = entity.last_seen_at {
+            if Utc::now().signed_duration_since(last_seen_at).num_seconds() < interval as i64 {
+                return Ok(());
+            }
+        }
+
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                last_seen_at = NOW()
+            WHERE
+                id = $1 AND
+                realm_id = $2 AND
+                (last_seen_at IS NULL OR last_seen_at < NOW() - $3 * INTERVAL '1 second');
+        "#)
+        .bind(entity.id)
+        .bind(self.realm_id)
+        .bind(interval)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge(&mut self, grace_period: i32, limit: i64) -> Result<Vec<i32>, Error> {
+        Ok(
+            sqlx::query_scalar::<_, i32>(r#"
+                DELETE FROM
+                    account
+                WHERE
+                    id IN (
+                        SELECT
+                            id
+                        FROM
+                            account
+                        WHERE
+                            realm_id = $1 AND
+                            status = $2 AND
+                            deletion_requested_at < NOW() - $3 * INTERVAL '1 second'
+                        LIMIT
+                            $4
+                        FOR UPDATE SKIP LOCKED
+                    )
+                RETURNING
+                    id;
+            "#)
+            .bind(self.realm_id)
+            .bind(AccountStatus::PendingDeletion)
+            .bind(grace_period)
+            .bind(limit)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn delete(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                account
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_session(&mut self, account_id: i32, sudo_mode_window: i32) -> Result<SessionEntity, Error> {
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                last_login_at = NOW(),
+                last_seen_at = NOW()
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        self.insert_session(account_id, None, Some(sudo_mode_window), None).await
+    }
+
+    pub async fn create_impersonation_session(&mut self, account_id: i32, impersonator_id: i32, duration: i32) -> Result<SessionEntity, Error> {
+        self.insert_session(account_id, Some(impersonator_id), None, Some(duration)).await
+    }
+
+    async fn insert_session(&mut self, account_id: i32, impersonator_id: Option<i32>, sudo_mode_window: Option<i32>, duration: Option<i32>) -> Result<SessionEntity, Error> {
+        let write_key = Uuid::new_v4().to_string();
+        let read_key = Uuid::new_v4().to_string();
+        let revoke_token = Uuid::new_v4().to_string();
+
+        Ok(
+            sqlx::query_as::<_, SessionEntity>(r#"
+                INSERT INTO
+                    session(
+                        realm_id,
+                        account_id,
+                        write_key,
+                        read_key,
+                        revoke_token,
+                        impersonator_id,
+                        elevated_until,
+                        expires_at
+                    )
+                VALUES
+                    ($1,$2,$3,$4,$5,$6,NOW() + $7 * INTERVAL '1 second',NOW() + $8 * INTERVAL '1 second')
+                RETURNING
+                    id,
+                    account_id,
+                    write_key,
+                    read_key,
+                    revoke_token,
+                    organization_id,
+                    impersonator_id,
+                    COALESCE(elevated_until > NOW(), FALSE) AS elevated;
+            "#)
+            .bind(self.realm_id)
+            .bind(account_id)
+            .bind(write_key)
+            .bind(read_key)
+            .bind(revoke_token)
+            .bind(impersonator_id)
+            .bind(sudo_mode_window)
+            .bind(duration)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_session_by_write_key(&mut self, write_key: &String) -> Result<Option<SessionEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, SessionEntity>(r#"
+                SELECT
+                    id,
+                    account_id,
+                    write_key,
+                    read_key,
+                    revoke_token,
+                    organization_id,
+                    impersonator_id,
+                    COALESCE(elevated_until > NOW(), FALSE) AS elevated
+                FROM
+                    session
+                WHERE
+                    write_key = $1 AND
+                    realm_id = $2 AND
+                    (expires_at IS NULL OR expires_at > NOW());
+            "#)
+            .bind(write_key)
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_session_by_read_key(&mut self, read_key: &String) -> Result<Option<SessionEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, SessionEntity>(r#"
+                SELECT
+                    id,
+                    account_id,
+                    write_key,
+                    read_key,
+                    revoke_token,
+                    organization_id,
+                    impersonator_id,
+                    COALESCE(elevated_until > NOW(), FALSE) AS elevated
+                FROM
+                    session
+                WHERE
+                    read_key = $1 AND
+                    realm_id = $2 AND
+                    (expires_at IS NULL OR expires_at > NOW());
+            "#)
+            .bind(read_key)
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn set_active_organization(&mut self, id: i32, organization_id: Option<i32>) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                session
+            SET
+                organization_id = $1
+            WHERE
+                id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(organization_id)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn elevate_session(&mut self, id: i32, sudo_mode_window: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                session
+            SET
+                elevated_until = NOW() + $1 * INTERVAL '1 second'
+            WHERE
+                id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(sudo_mode_window)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_session(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                session
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_session(&mut self, revoke_token: &String) -> Result<Option<i32>, Error> {
+        Ok(
+            sqlx::query_scalar::<_, i32>(r#"
+                DELETE FROM
+                    session
+                WHERE
+                    revoke_token = $1 AND
+                    realm_id = $2
+                RETURNING
+                    account_id;
+            "#)
+            .bind(revoke_token)
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn delete_expired_sessions(&mut self) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                session
+            WHERE
+                expires_at < NOW() AND
+                realm_id = $1;
+        "#)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_sessions(&mut self, account_id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                session
+            WHERE
+                account_id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_credentials(&mut self, account_id: i32) -> Result<(), Error> {
+        self.delete_sessions(account_id).await?;
+
+        sqlx::query(r#"
+            DELETE FROM
+                login_link
+            WHERE
+                account_id = $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2);
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            UPDATE
+                login_code
+            SET
+                revoked_at = NOW()
+            WHERE
+                account_id = $1 AND
+                used_at IS NULL AND
+                revoked_at IS NULL AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2);
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            DELETE FROM
+                email_change
+            WHERE
+                account_id = $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2);
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn reset_password(&mut self, account_id: i32) -> Result<(), Error> {
+        self.store_password(account_id, &Uuid::new_v4().to_string(), true).await?;
+
+        sqlx::query(r#"
+            UPDATE
+                account
+            SET
+                password_change_required = TRUE
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        self.revoke_credentials(account_id).await
+    }
+
+    pub async fn find_sessions(&mut self, account_id: i32) -> Result<Vec<SessionEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, SessionEntity>(r#"
+                SELECT
+                    id,
+                    account_id,
+                    write_key,
+                    read_key,
+                    revoke_token,
+                    organization_id,
+                    impersonator_id,
+                    COALESCE(elevated_until > NOW(), FALSE) AS elevated
+                FROM
+                    session
+                WHERE
+                    account_id = $1 AND
+                    realm_id = $2 AND
+                    (expires_at IS NULL OR expires_at > NOW())
+                ORDER BY
+                    id;
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_devices(&mut self, account_id: i32) -> Result<Vec<DeviceEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, DeviceEntity>(r#"
+                SELECT
+                    *
+                FROM
+                    device
+                WHERE
+                    account_id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $2)
+                ORDER BY
+                    created_at;
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn remember_device(&mut self, account_id: i32, client: &ClientExtractor) -> Result<bool, Error> {
+        let result = sqlx::query(r#"
+            UPDATE
+                device
+            SET
+                last_seen_at = NOW()
+            WHERE
+                account_id = $1 AND
+                ip_address IS NOT DISTINCT FROM $2 AND
+                user_agent IS NOT DISTINCT FROM $3;
+        "#)
+        .bind(account_id)
+        .bind(&client.ip_address)
+        .bind(&client.user_agent)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(r#"
+            INSERT INTO
+                device(
+                    account_id,
+                    ip_address,
+                    user_agent
+                )
+            VALUES
+                ($1,$2,$3);
+        "#)
+        .bind(account_id)
+        .bind(&client.ip_address)
+        .bind(&client.user_agent)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(true)
+    }
+
+    pub async fn login_link_allowed(&mut self, account_id: i32) -> Result<bool, Error> {
+        Ok(
+            sqlx::query_scalar::<_, bool>(r#"
+                SELECT
+                    COUNT(*) < 5
+                FROM
+                    login_link
+                WHERE
+                    account_id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $2) AND
+                    created_at > NOW() - INTERVAL '1 hour';
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn create_login_link(&mut self, account_id: i32) -> Result<String, Error> {
+        let token = Uuid::new_v4().to_string();
+
+        sqlx::query(r#"
+            INSERT INTO
+                login_link(
+                    account_id,
+                    token_hash
+                )
+            VALUES
+                ($1,$2);
+        "#)
+        .bind(account_id)
+        .bind(hex::encode(Sha256::digest(&token)))
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn consume_login_link(&mut self, token: &String) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            WITH consumed AS (
+                UPDATE
+                    login_link
+                SET
+                    used_at = NOW()
+                WHERE
+                    token_hash = $1 AND
+                    used_at IS NULL AND
+                    created_at > NOW() - INTERVAL '15 minutes'
+                RETURNING
+                    account_id
+            )
+            SELECT
+                account.*
+            FROM
+                account
+            INNER JOIN
+                consumed ON consumed.account_id = account.id
+            WHERE
+                account.realm_id = $2;
+        "#)
+        .bind(hex::encode(Sha256::digest(token)))
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn login_code_allowed(&mut self, account_id: i32) -> Result<bool, Error> {
+        Ok(
+            sqlx::query_scalar::<_, bool>(r#"
+                SELECT
+                    COUNT(*) < 5 AND COALESCE(SUM(attempts), 0) < 10
+                FROM
+                    login_code
+                WHERE
+                    account_id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $2) AND
+                    created_at > NOW() - INTERVAL '1 hour';
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn create_login_code(&mut self, account_id: i32, challenge: &String) -> Result<String, Error> {
+        let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+
+        sqlx::query(r#"
+            UPDATE
+                login_code
+            SET
+                revoked_at = NOW()
+            WHERE
+                account_id = $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2) AND
+                used_at IS NULL AND
+                revoked_at IS NULL;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            INSERT INTO
+                login_code(
+                    account_id,
+                    challenge_hash,
+                    code_hash
+                )
+            VALUES
+                ($1,$2,$3);
+        "#)
+        .bind(account_id)
+        .bind(hex::encode(Sha256::digest(challenge)))
+        .bind(hash(&code, DEFAULT_COST)?)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(code)
+    }
+
+    pub async fn find_login_code(&mut self, challenge: &String) -> Result<Option<LoginCodeEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, LoginCodeEntity>(r#"
+                SELECT
+                    login_code.id,
+                    login_code.account_id,
+                    login_code.code_hash,
+                    login_code.attempts
+                FROM
+                    login_code
+                INNER JOIN
+                    account ON account.id = login_code.account_id
+                WHERE
+                    login_code.challenge_hash = $1 AND
+                    login_code.used_at IS NULL AND
+                    login_code.revoked_at IS NULL AND
+                    login_code.attempts < 5 AND
+                    login_code.created_at > NOW() - INTERVAL '10 minutes' AND
+                    account.realm_id = $2 AND
+                    (
+                        SELECT
+                            SUM(recent.attempts)
+                        FROM
+                            login_code AS recent
+                        WHERE
+                            recent.account_id = login_code.account_id AND
+                            recent.created_at > NOW() - INTERVAL '1 hour'
+                    ) < 10
+                FOR UPDATE OF login_code;
+            "#)
+            .bind(hex::encode(Sha256::digest(challenge)))
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn delete_expired_login_codes(&mut self) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                login_code
+            WHERE
+                created_at < NOW() - INTERVAL '1 hour' AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $1);
+        "#)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fail_login_code(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                login_code
+            SET
+                attempts = attempts + 1
+            WHERE
+                id = $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2);
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn use_login_code(&mut self, id: i32) -> Result<Option<AccountEntity>, Error> {
+        let entity = sqlx::query_as::<_, AccountEntity>(r#"
+            WITH used AS (
+                UPDATE
+                    login_code
+                SET
+                    used_at = NOW()
+                WHERE
+                    id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $2)
+                RETURNING
+                    account_id
+            )
+            SELECT
+                account.*
+            FROM
+                account
+            INNER JOIN
+                used ON used.account_id = account.id;
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_account(entity)).transpose()
+    }
+
+    pub async fn create_email_change(&mut self, entity: &AccountEntity, new_email: &str) -> Result<EmailChangeEntity, Error> {
+        let confirm_token = Uuid::new_v4().to_string();
+        let undo_token = Uuid::new_v4().to_string();
+
+        sqlx::query(r#"
+            DELETE FROM
+                email_change
+            WHERE
+                account_id = $1 AND
+                confirmed_at IS NULL;
+        "#)
+        .bind(entity.id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        let (old_email_ciphertext, email_key_id) = self.cipher.encrypt(&entity.email)?;
+        let (new_email_ciphertext, _) = self.cipher.encrypt(new_email)?;
+
+        let entity = sqlx::query_as::<_, EmailChangeEntity>(r#"
+            INSERT INTO
+                email_change(
+                    account_id,
+                    old_email_ciphertext,
+                    new_email_ciphertext,
+                    email_key_id,
+                    confirm_token,
+                    undo_token
+                )
+            VALUES
+                ($1,$2,$3,$4,$5,$6)
+            RETURNING
+                *;
+        "#)
+        .bind(entity.id)
+        .bind(old_email_ciphertext)
+        .bind(new_email_ciphertext)
+        .bind(email_key_id)
+        .bind(confirm_token)
+        .bind(undo_token)
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        self.decrypt_email_change(entity)
+    }
+
+    pub async fn confirm_email_change(&mut self, confirm_token: &String) -> Result<Option<EmailChangeEntity>, Error> {
+        let entity = sqlx::query_as::<_, EmailChangeEntity>(r#"
+            UPDATE
+                email_change
+            SET
+                confirmed_at = NOW()
+            WHERE
+                confirm_token = $1 AND
+                confirmed_at IS NULL AND
+                created_at > NOW() - INTERVAL '1 day' AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2)
+            RETURNING
+                *;
+        "#)
+        .bind(confirm_token)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_email_change(entity)).transpose()
+    }
+
+    pub async fn undo_email_change(&mut self, undo_token: &String) -> Result<Option<EmailChangeEntity>, Error> {
+        let entity = sqlx::query_as::<_, EmailChangeEntity>(r#"
+            UPDATE
+                email_change
+            SET
+                undone_at = NOW()
+            WHERE
+                undo_token = $1 AND
+                undone_at IS NULL AND
+                confirmed_at > NOW() - INTERVAL '7 days' AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $2)
+            RETURNING
+                *;
+        "#)
+        .bind(undo_token)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_email_change(entity)).transpose()
+    }
+
+    pub async fn find_accounts(&mut self, after: i32, limit: i64) -> Result<Vec<AccountEntity>, Error> {
+        let entities = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                id > $1 AND
+                realm_id = $3
+            ORDER BY
+                id
+            LIMIT
+                $2;
+        "#)
+        .bind(after)
+        .bind(limit)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        entities.into_iter().map(|entity| self.decrypt_account(entity)).collect()
+    }
+
+    pub async fn search_accounts(&mut self, email: &Option<String>, username: &Option<String>, status: Option<AccountStatus>, after: Option<Uuid>, limit: i64) -> Result<Vec<AccountEntity>, Error> {
+        let pattern = |prefix: &String| {
+            format!("{}%", prefix.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+        };
+
+        let prefix = email.as_ref().map(|email| email.trim().to_lowercase());
+        let mut accounts = Vec::new();
+        let mut after = after;
+
+        loop {
+            let entities = sqlx::query_as::<_, AccountEntity>(r#"
+                SELECT
+                    *
+                FROM
+                    account
+                WHERE
+                    realm_id = $1 AND
+                    (
+                        $2::VARCHAR IS NULL OR
+                        id IN (SELECT account_id FROM account_email_prefix WHERE prefix_index = $2) OR
+                        (email_index IS NULL AND LOWER(email) LIKE $7)
+                    ) AND
+                    ($3::VARCHAR IS NULL OR LOWER(username) LIKE $3) AND
+                    ($4::VARCHAR IS NULL OR status = $4) AND
+                    ($5::UUID IS NULL OR public_id > $5)
+                ORDER BY
+                    public_id
+                LIMIT
+                    $6;
+            "#)
+            .bind(self.realm_id)
+            .bind(email.as_ref().map(|email| self.cipher.email_prefix_index(email)))
+            .bind(username.as_ref().map(pattern))
+            .bind(status)
+            .bind(after)
+            .bind(limit)
+            .bind(email.as_ref().map(pattern))
+            .fetch_all(&mut *self.transaction)
+            .await?;
+
+            let fetched = entities.len() as i64;
+
+            after = entities.last().map(|entity| entity.public_id).or(after);
+
+            for entity in entities {
+                let entity = self.decrypt_account(entity)?;
+
+                if prefix.as_ref().is_none_or(|prefix| entity.email.to_lowercase().starts_with(prefix)) {
+                    accounts.push(entity);
+                }
+            }
+
+            if fetched < limit || accounts.len() as i64 >= limit {
+                break;
+            }
+        }
+
+        accounts.truncate(limit as usize);
+
+        Ok(accounts)
+    }
+
+    pub async fn normalize_email(&mut self, entity: &AccountEntity) -> Result<Option<AccountEntity>, Error> {
+        let email = normalize_email(&entity.email);
+
+        let collision = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                email_index = $1 AND
+                id <> $2 AND
+                realm_id = $3;
+        "#)
+        .bind(self.cipher.email_index(&email))
+        .bind(entity.id)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        if let Some(collision) = collision {
+            return Ok(Some(self.decrypt_account(collision)?));
+        }
+
+        self.store_email(entity.id, &email, false).await?;
+
+        Ok(None)
+    }
+
+    pub async fn merge_accounts(&mut self, id: i32, duplicate_id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO
+                account_role(account_id, role_id)
+            SELECT
+                $1, role_id
+            FROM
+                account_role
+            WHERE
+                account_id = $2
+            ON CONFLICT DO NOTHING;
+        "#)
+        .bind(id)
+        .bind(duplicate_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            INSERT INTO
+                membership(organization_id, account_id, role)
+            SELECT
+                organization_id, $1, role
+            FROM
+                membership
+            WHERE
+                account_id = $2
+            ON CONFLICT DO NOTHING;
+        "#)
+        .bind(id)
+        .bind(duplicate_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            DELETE FROM
+                account
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(duplicate_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn rotate_email_keys(&mut self, limit: i64) -> Result<usize, Error> {
+        let entities = sqlx::query_as::<_, AccountEntity>(r#"
+            SELECT
+                *
+            FROM
+                account
+            WHERE
+                (
+                    email_key_id IS DISTINCT FROM $1 OR
+                    NOT EXISTS (SELECT 1 FROM account_email_prefix WHERE account_id = account.id)
+                ) AND
+                realm_id = $3
+            LIMIT
+                $2
+            FOR UPDATE SKIP LOCKED;
+        "#)
+        .bind(self.cipher.key_id())
+        .bind(limit)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        let changes = sqlx::query_as::<_, EmailChangeEntity>(r#"
+            SELECT
+                *
+            FROM
+                email_change
+            WHERE
+                email_key_id <> $1 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $3)
+            LIMIT
+                $2
+            FOR UPDATE SKIP LOCKED;
+        "#)
+        .bind(self.cipher.key_id())
+        .bind(limit)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        let invitations = sqlx::query_as::<_, InvitationEntity>(r#"
+            SELECT
+                invitation.id,
+                invitation.organization_id,
+                organization.name AS organization_name,
+                invitation.email_ciphertext,
+                invitation.email_key_id,
+                invitation.role
+            FROM
+                invitation
+            INNER JOIN
+                organization ON organization.id = invitation.organization_id
+            WHERE
+                invitation.email_key_id <> $1 AND
+                organization.realm_id = $3
+            LIMIT
+                $2
+            FOR UPDATE OF invitation SKIP LOCKED;
+        "#)
+        .bind(self.cipher.key_id())
+        .bind(limit)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        let count = entities.len() + changes.len() + invitations.len();
+
+        for entity in entities {
+            let entity = self.decrypt_account(entity)?;
+            self.store_email(entity.id, &entity.email, false).await?;
+        }
+
+        for change in changes {
+            let change = self.decrypt_email_change(change)?;
+            let (old_email_ciphertext, email_key_id) = self.cipher.encrypt(&change.old_email)?;
+            let (new_email_ciphertext, _) = self.cipher.encrypt(&change.new_email)?;
+
+            sqlx::query(r#"
+                UPDATE
+                    email_change
+                SET
+                    old_email_ciphertext = $1,
+                    new_email_ciphertext = $2,
+                    email_key_id = $3
+                WHERE
+                    id = $4;
+            "#)
+            .bind(old_email_ciphertext)
+            .bind(new_email_ciphertext)
+            .bind(email_key_id)
+            .bind(change.id)
+            .execute(&mut *self.transaction)
+            .await?;
+        }
+
+        for invitation in invitations {
+            let invitation = self.decrypt_invitation(invitation)?;
+            let (email_ciphertext, email_key_id) = self.cipher.encrypt(&invitation.email)?;
+
+            sqlx::query(r#"
+                UPDATE
+                    invitation
+                SET
+                    email_ciphertext = $1,
+                    email_key_id = $2
+                WHERE
+                    id = $3;
+            "#)
+            .bind(email_ciphertext)
+            .bind(email_key_id)
+            .bind(invitation.id)
+            .execute(&mut *self.transaction)
+            .await?;
+        }
+
+        Ok(count)
+    }
+
+    pub async fn find_roles(&mut self, account_id: i32) -> Result<Vec<String>, Error> {
+        Ok(
+            sqlx::query_scalar::<_, String>(r#"
+                SELECT
+                    role.name
+                FROM
+                    role
+                INNER JOIN
+                    account_role ON account_role.role_id = role.id
+                WHERE
+                    account_role.account_id = $1 AND
+                    account_role.account_id IN (SELECT id FROM account WHERE realm_id = $2)
+                ORDER BY
+                    role.name;
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_permissions(&mut self, account_id: i32) -> Result<Vec<String>, Error> {
+        Ok(
+            sqlx::query_scalar::<_, String>(r#"
+                SELECT DISTINCT
+                    permission.name
+                FROM
+                    permission
+                INNER JOIN
+                    role_permission ON role_permission.permission_id = permission.id
+                INNER JOIN
+                    account_role ON account_role.role_id = role_permission.role_id
+                WHERE
+                    account_role.account_id = $1 AND
+                    account_role.account_id IN (SELECT id FROM account WHERE realm_id = $2)
+                ORDER BY
+                    permission.name;
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn role_exists(&mut self, role: &String) -> Result<bool, Error> {
+        Ok(
+            sqlx::query_scalar::<_, bool>(r#"
+                SELECT EXISTS(
+                    SELECT
+                        1
+                    FROM
+                        role
+                    WHERE
+                        name = $1
+                );
+            "#)
+            .bind(role)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn assign_role(&mut self, account_id: i32, role: &String) -> Result<bool, Error> {
+        let result = sqlx::query(r#"
+            INSERT INTO
+                account_role(
+                    account_id,
+                    role_id
+                )
+            SELECT
+                account.id,
+                role.id
+            FROM
+                account,
+                role
+            WHERE
+                account.id = $1 AND
+                account.realm_id = $2 AND
+                role.name = $3
+            ON CONFLICT DO NOTHING;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .bind(role)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn revoke_role(&mut self, account_id: i32, role: &String) -> Result<bool, Error> {
+        let result = sqlx::query(r#"
+            DELETE FROM
+                account_role
+            USING
+                account,
+                role
+            WHERE
+                account_role.account_id = account.id AND
+                account_role.role_id = role.id AND
+                account.id = $1 AND
+                account.realm_id = $2 AND
+                role.name = $3;
+        "#)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .bind(role)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_organization(&mut self, account_id: i32, name: &String) -> Result<OrganizationEntity, Error> {
+        Ok(
+            sqlx::query_as::<_, OrganizationEntity>(r#"
+                WITH organization AS (
+                    INSERT INTO
+                        organization(
+                            realm_id,
+                            name
+                        )
+                    VALUES
+                        ($4,$1)
+                    RETURNING
+                        id,
+                        name
+                ), membership AS (
+                    INSERT INTO
+                        membership(
+                            organization_id,
+                            account_id,
+                            role
+                        )
+                    SELECT
+                        id,
+                        $2,
+                        $3
+                    FROM
+                        organization
+                )
+                SELECT
+                    id,
+                    name,
+                    $3 AS role
+                FROM
+                    organization;
+            "#)
+            .bind(name)
+            .bind(account_id)
+            .bind(OrganizationRole::Owner)
+            .bind(self.realm_id)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_organizations(&mut self, account_id: i32) -> Result<Vec<OrganizationEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, OrganizationEntity>(r#"
+                SELECT
+                    organization.id,
+                    organization.name,
+                    membership.role
+                FROM
+                    organization
+                INNER JOIN
+                    membership ON membership.organization_id = organization.id
+                WHERE
+                    membership.account_id = $1 AND
+                    organization.realm_id = $2
+                ORDER BY
+                    organization.name;
+            "#)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_organization(&mut self, id: i32, account_id: i32) -> Result<Option<OrganizationEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, OrganizationEntity>(r#"
+                SELECT
+                    organization.id,
+                    organization.name,
+                    membership.role
+                FROM
+                    organization
+                INNER JOIN
+                    membership ON membership.organization_id = organization.id
+                WHERE
+                    organization.id = $1 AND
+                    organization.realm_id = $3 AND
+                    membership.account_id = $2;
+            "#)
+            .bind(id)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn update_organization(&mut self, id: i32, name: &String) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                organization
+            SET
+                name = $1
+            WHERE
+                id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(name)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_organization(&mut self, id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                organization
+            WHERE
+                id = $1 AND
+                realm_id = $2;
+        "#)
+        .bind(id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_memberships(&mut self, organization_id: i32) -> Result<Vec<MembershipEntity>, Error> {
+        let entities = sqlx::query_as::<_, MembershipEntity>(r#"
+            SELECT
+                account.*,
+                membership.role
+            FROM
+                membership
+            INNER JOIN
+                account ON account.id = membership.account_id
+            WHERE
+                membership.organization_id = $1 AND
+                account.realm_id = $2
+            ORDER BY
+                account.id;
+        "#)
+        .bind(organization_id)
+        .bind(self.realm_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        entities.into_iter().map(|mut entity| {
+            entity.account = self.decrypt_account(entity.account)?;
+            Ok(entity)
+        }).collect()
+    }
+
+    pub async fn find_membership_role(&mut self, organization_id: i32, account_id: i32) -> Result<Option<OrganizationRole>, Error> {
+        Ok(
+            sqlx::query_scalar::<_, OrganizationRole>(r#"
+                SELECT
+                    membership.role
+                FROM
+                    membership
+                INNER JOIN
+                    organization ON organization.id = membership.organization_id
+                WHERE
+                    membership.organization_id = $1 AND
+                    membership.account_id = $2 AND
+                    organization.realm_id = $3
+                FOR UPDATE OF membership;
+            "#)
+            .bind(organization_id)
+            .bind(account_id)
+            .bind(self.realm_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn count_owners(&mut self, organization_id: i32) -> Result<i64, Error> {
+        sqlx::query(r#"
+            SELECT
+                id
+            FROM
+                organization
+            WHERE
+                id = $1 AND
+                realm_id = $2
+            FOR UPDATE;
+        "#)
+        .bind(organization_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(
+            sqlx::query_scalar::<_, i64>(r#"
+                SELECT
+                    COUNT(*)
+                FROM
+                    membership
+                WHERE
+                    organization_id = $1 AND
+                    role = $2 AND
+                    organization_id IN (SELECT id FROM organization WHERE realm_id = $3);
+            "#)
+            .bind(organization_id)
+            .bind(OrganizationRole::Owner)
+            .bind(self.realm_id)
+            .fetch_one(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn update_membership(&mut self, organization_id: i32, account_id: i32, role: OrganizationRole) -> Result<(), Error> {
+        sqlx::query(r#"
+            UPDATE
+                membership
+            SET
+                role = $1
+            WHERE
+                organization_id = $2 AND
+                account_id = $3 AND
+                organization_id IN (SELECT id FROM organization WHERE realm_id = $4);
+        "#)
+        .bind(role)
+        .bind(organization_id)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_membership(&mut self, organization_id: i32, account_id: i32) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                membership
+            WHERE
+                organization_id = $1 AND
+                account_id = $2 AND
+                organization_id IN (SELECT id FROM organization WHERE realm_id = $3);
+        "#)
+        .bind(organization_id)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        sqlx::query(r#"
+            UPDATE
+                session
+            SET
+                organization_id = NULL
+            WHERE
+                organization_id = $1 AND
+                account_id = $2 AND
+                realm_id = $3;
+        "#)
+        .bind(organization_id)
+        .bind(account_id)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_invitation(&mut self, organization_id: i32, email: &str, role: OrganizationRole) -> Result<String, Error> {
+        let token = Uuid::new_v4().to_string();
+        let (email_ciphertext, email_key_id) = self.cipher.encrypt(email)?;
+
+        sqlx::query(r#"
+            INSERT INTO
+                invitation(
+                    organization_id,
+                    email_ciphertext,
+                    email_key_id,
+                    email_index,
+                    role,
+                    token_hash
+                )
+            VALUES
+                ($1,$2,$3,$4,$5,$6)
+            ON CONFLICT (organization_id, email_index) DO UPDATE SET
+                email_ciphertext = EXCLUDED.email_ciphertext,
+                email_key_id = EXCLUDED.email_key_id,
+                role = EXCLUDED.role,
+                token_hash = EXCLUDED.token_hash,
+                created_at = NOW();
+        "#)
+        .bind(organization_id)
+        .bind(email_ciphertext)
+        .bind(email_key_id)
+        .bind(self.cipher.email_index(email))
+        .bind(role)
+        .bind(hex::encode(Sha256::digest(&token)))
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn consume_invitation(&mut self, token: &String) -> Result<Option<InvitationEntity>, Error> {
+        let entity = sqlx::query_as::<_, InvitationEntity>(r#"
+            DELETE FROM
+                invitation
+            USING
+                organization
+            WHERE
+                organization.id = invitation.organization_id AND
+                organization.realm_id = $2 AND
+                invitation.token_hash = $1 AND
+                invitation.created_at > NOW() - INTERVAL '7 days'
+            RETURNING
+                invitation.id,
+                invitation.organization_id,
+                organization.name AS organization_name,
+                invitation.email_ciphertext,
+                invitation.email_key_id,
+                invitation.role;
+        "#)
+        .bind(hex::encode(Sha256::digest(token)))
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        entity.map(|entity| self.decrypt_invitation(entity)).transpose()
+    }
+
+    pub async fn create_membership(&mut self, organization_id: i32, account_id: i32, role: OrganizationRole) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO
+                membership(
+                    organization_id,
+                    account_id,
+                    role
+                )
+            VALUES
+                ($1,$2,$3)
+            ON CONFLICT DO NOTHING;
+        "#)
+        .bind(organization_id)
+        .bind(account_id)
+        .bind(role)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_export(&mut self, account_id: i32) -> Result<String, Error> {
+        let token = Uuid::new_v4().to_string();
+
+        sqlx::query(r#"
+            INSERT INTO
+                export(
+                    account_id,
+                    token_hash
+                )
+            VALUES
+                ($1,$2);
+        "#)
+        .bind(account_id)
+        .bind(hex::encode(Sha256::digest(&token)))
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn complete_export(&mut self, token: &String, archive: Option<Vec<u8>>) -> Result<(), Error> {
+        let (status, archive, archive_key_id) = match archive {
+            Some(archive) => {
+                let (archive, archive_key_id) = self.cipher.encrypt_bytes(&archive)?;
+
+                (ExportStatus::Ready, Some(archive), Some(archive_key_id))
+            },
+            None => (ExportStatus::Failed, None, None)
+        };
+
+        sqlx::query(r#"
+            UPDATE
+                export
+            SET
+                status = $1,
+                archive = $2,
+                archive_key_id = $3,
+                completed_at = NOW()
+            WHERE
+                token_hash = $4 AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $5);
+        "#)
+        .bind(status)
+        .bind(archive)
+        .bind(archive_key_id)
+        .bind(hex::encode(Sha256::digest(token)))
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_exports(&mut self, account_id: i32) -> Result<Vec<ExportEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, ExportEntity>(r#"
+                SELECT
+                    id,
+                    account_id,
+                    CASE WHEN status = $2 AND created_at < NOW() - INTERVAL '1 hour' THEN $3 ELSE status END AS status,
+                    created_at,
+                    completed_at
+                FROM
+                    export
+                WHERE
+                    account_id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $4)
+                ORDER BY
+                    created_at DESC;
+            "#)
+            .bind(account_id)
+            .bind(ExportStatus::Pending)
+            .bind(ExportStatus::Failed)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn find_export_archive(&mut self, account_id: i32, token: &String) -> Result<Option<Vec<u8>>, Error> {
+        let archive = sqlx::query_as::<_, (Vec<u8>, String)>(r#"
+            SELECT
+                export.archive,
+                export.archive_key_id
+            FROM
+                export
+            INNER JOIN
+                account ON account.id = export.account_id
+            WHERE
+                export.account_id = $1 AND
+                export.token_hash = $2 AND
+                export.status = $3 AND
+                export.completed_at > NOW() - INTERVAL '7 days' AND
+                account.realm_id = $4;
+        "#)
+        .bind(account_id)
+        .bind(hex::encode(Sha256::digest(token)))
+        .bind(ExportStatus::Ready)
+        .bind(self.realm_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        match archive {
+            Some((archive, archive_key_id)) => Ok(Some(self.cipher.decrypt_bytes(&archive, &archive_key_id)?)),
+            None => Ok(None)
+        }
+    }
+
+    pub async fn delete_expired_exports(&mut self) -> Result<(), Error> {
+        sqlx::query(r#"
+            DELETE FROM
+                export
+            WHERE
+                created_at < NOW() - INTERVAL '7 days' AND
+                account_id IN (SELECT id FROM account WHERE realm_id = $1);
+        "#)
+        .bind(self.realm_id)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn audit(&mut self, account_id: Option<i32>, action: AuditAction, outcome: AuditOutcome, client: &ClientExtractor) -> Result<(), Error> {
+        self.insert_audit_event(None, account_id, action, outcome, client).await
+    }
+
+    pub async fn audit_as(&mut self, actor_id: i32, account_id: Option<i32>, action: AuditAction, outcome: AuditOutcome, client: &ClientExtractor) -> Result<(), Error> {
+        self.insert_audit_event(Some(actor_id), account_id, action, outcome, client).await
+    }
+
+    async fn insert_audit_event(&mut self, actor_id: Option<i32>, account_id: Option<i32>, action: AuditAction, outcome: AuditOutcome, client: &ClientExtractor) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO
+                audit_event(
+                    actor_id,
+                    account_id,
+                    action,
+                    outcome,
+                    ip_address,
+                    user_agent
+                )
+            VALUES
+                ($1,$2,$3,$4,$5,$6);
+        "#)
+        .bind(actor_id.or(self.impersonator_id))
+        .bind(account_id)
+        .bind(action)
+        .bind(outcome)
+        .bind(&client.ip_address)
+        .bind(&client.user_agent)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_audit_events(&mut self, account_id: i32, limit: i64) -> Result<Vec<AuditEventEntity>, Error> {
+        Ok(
+            sqlx::query_as::<_, AuditEventEntity>(r#"
+                SELECT
+                    *
+                FROM
+                    audit_event
+                WHERE
+                    account_id = $1 AND
+                    account_id IN (SELECT id FROM account WHERE realm_id = $3)
+                ORDER BY
+                    created_at DESC
+                LIMIT
+                    $2;
+            "#)
+            .bind(account_id)
+            .bind(limit)
+            .bind(self.realm_id)
+            .fetch_all(&mut *self.transaction)
+            .await?
+        )
+    }
+
+    pub async fn commit(self) -> Result<(), Error> {
+        self.transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn savepoint(&mut self) -> Result<(), Error> {
+        sqlx::query("SAVEPOINT account_savepoint;").execute(&mut *self.transaction).await?;
+        Ok(())
+    }
+
+    pub async fn release_savepoint(&mut self) -> Result<(), Error> {
+        sqlx::query("RELEASE SAVEPOINT account_savepoint;").execute(&mut *self.transaction).await?;
+        Ok(())
+    }
+
+    pub async fn rollback_to_savepoint(&mut self) -> Result<(), Error> {
+        sqlx::query("ROLLBACK TO SAVEPOINT account_savepoint;").execute(&mut *self.transaction).await?;
+        Ok(())
+    }
+
+    pub async fn rollback(self) -> Result<(), Error> {
+        self.transaction.rollback().await?;
         Ok(())
     }
 }